@@ -1,9 +1,31 @@
+// Anchor's `#[program]` generates its IDL instructions in a `__private` module at the
+// crate root, and one of them calls the deprecated `AccountInfo::realloc`. No item-level
+// allow reaches that module, so the lint is relaxed here.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Burn as SplBurn, MintTo as SplMintTo, Transfer as SplTransfer, 
-            SetAuthority as SplSetAuthority, FreezeAccount as SplFreezeAccount, ThawAccount as SplThawAccount,
-            CloseAccount as SplCloseAccount, spl_token::instruction::AuthorityType},
+    token_interface::{self, Mint, TokenAccount, TokenInterface, SetAuthority as SplSetAuthority,
+            FreezeAccount as SplFreezeAccount, ThawAccount as SplThawAccount, CloseAccount as SplCloseAccount, Approve as SplApprove,
+            Revoke as SplRevoke, TransferChecked as SplTransferChecked, MintToChecked as SplMintToChecked,
+            BurnChecked as SplBurnChecked, spl_token_2022::instruction::AuthorityType,
+            spl_token_2022::state::Account as SplTokenAccount},
+    token_2022::spl_token_2022::{
+        extension::{default_account_state::DefaultAccountState, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions},
+        state::{AccountState, Mint as SplMint},
+    },
+    token_2022_extensions::{default_account_state_initialize, default_account_state_update,
+            DefaultAccountStateInitialize, DefaultAccountStateUpdate},
+};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer as SystemTransfer};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{CreateMetadataAccountV3CpiBuilder, UpdateMetadataAccountV2CpiBuilder},
+    types::{Collection, Creator, DataV2, UseMethod, Uses},
 };
 
 declare_id!("72LBxzy5ggcs4PPvet1m8vhPGfuthxPHxtwE4xsSo8qP");
@@ -52,6 +74,134 @@ pub enum TokenError {
     BatchLimitExceeded,
     #[msg("Invalid mint configuration")]
     InvalidMintConfig,
+    #[msg("Remaining accounts do not match the batch destinations")]
+    InvalidRemainingAccounts,
+    #[msg("Associated token account does not match recipient and mint")]
+    InvalidAssociatedTokenAccount,
+    #[msg("Invalid metadata: at most 5 creators with shares summing to 100")]
+    InvalidCreators,
+    #[msg("Invalid metadata account")]
+    InvalidMetadataAccount,
+    #[msg("Token program is not supported by this build")]
+    UnsupportedTokenProgram,
+    #[msg("Supply cap exceeded: minting would push supply past max_supply")]
+    SupplyCapExceeded,
+    #[msg("Supply cap can only be lowered, and not below the current supply")]
+    InvalidSupplyCap,
+    #[msg("Invalid multisig: 1 to 10 unique signers and a threshold between 1 and the signer count")]
+    InvalidMultisigConfig,
+    #[msg("Signer is not a member of the multisig")]
+    NotMultisigSigner,
+    #[msg("Proposal already approved by this signer")]
+    AlreadyApproved,
+    #[msg("Proposal does not have enough approvals")]
+    ThresholdNotMet,
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Accounts do not match the proposal action")]
+    InvalidProposalAccounts,
+    #[msg("Authority changes must be proposed and accepted; only revocation is immediate")]
+    AuthorityChangeRequiresAcceptance,
+    #[msg("Authority changes on this mint are timelocked and must be queued")]
+    AuthorityChangeTimelocked,
+    #[msg("Queued authority change is still timelocked")]
+    TimelockNotExpired,
+    #[msg("Authority delay can only be raised, up to 30 days")]
+    InvalidAuthorityDelay,
+    #[msg("Signer does not hold the required role")]
+    MissingRole,
+    #[msg("Role allowance exceeded")]
+    RoleAllowanceExceeded,
+    #[msg("Role grant limit reached (max 16)")]
+    RoleGrantLimitExceeded,
+    #[msg("Role is not granted to this holder")]
+    RoleNotGranted,
+    #[msg("Cannot revoke the last admin role")]
+    LastAdminRole,
+    #[msg("Mint is paused")]
+    Paused,
+    #[msg("Rate limit exceeded: not enough minting capacity left in the window")]
+    RateLimitExceeded,
+    #[msg("Invalid rate limit: window and limits must be greater than 0, and a configured limit can only be tightened")]
+    InvalidRateLimit,
+    #[msg("Invalid airdrop: allocation, recipients (max 65536) and a future expiry are required")]
+    InvalidAirdropConfig,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Airdrop allocation already claimed")]
+    AlreadyClaimed,
+    #[msg("Airdrop has expired")]
+    AirdropExpired,
+    #[msg("Airdrop has not expired yet")]
+    AirdropNotExpired,
+    #[msg("Invalid vesting schedule: requires an amount and start <= cliff <= end with start < end")]
+    InvalidVestingSchedule,
+    #[msg("No vested tokens to release")]
+    NothingToRelease,
+    #[msg("Vesting is not revocable")]
+    VestingNotRevocable,
+    #[msg("Invalid sale: check the curve, window, and that the mint state PDA holds the mint authority")]
+    InvalidSaleConfig,
+    #[msg("Sale is not active")]
+    SaleNotActive,
+    #[msg("Purchase exceeds the per-wallet cap")]
+    WalletCapExceeded,
+    #[msg("Slippage exceeded: price moved past max_cost or min_proceeds")]
+    SlippageExceeded,
+    #[msg("Sell-back is not enabled for this sale")]
+    SellBackDisabled,
+    #[msg("Price calculation overflow")]
+    PriceOverflow,
+    #[msg("Invalid faucet: drip amount must be positive, cooldown non-negative, and the mint state PDA must hold the mint authority")]
+    InvalidFaucetConfig,
+    #[msg("Faucet is disabled")]
+    FaucetDisabled,
+    #[msg("Faucet cooldown has not elapsed for this wallet")]
+    FaucetCooldown,
+    #[msg("Amount exceeds the delegate's approved allowance")]
+    DelegateAllowanceExceeded,
+    #[msg("Token account has no delegate")]
+    NoDelegate,
+    #[msg("Decimals do not match the mint")]
+    DecimalsMismatch,
+    #[msg("Token-2022 mints require the checked instruction variant")]
+    CheckedInstructionRequired,
+    #[msg("Case ID too long")]
+    CaseIdTooLong,
+    #[msg("Invalid freeze record account")]
+    InvalidFreezeRecord,
+    #[msg("Wallet is on the mint's denylist")]
+    Denylisted,
+    #[msg("Signer is not the mint's compliance authority")]
+    InvalidComplianceAuthority,
+    #[msg("Recipient has no KYC attestation")]
+    AttestationMissing,
+    #[msg("Recipient's KYC attestation has expired")]
+    AttestationExpired,
+    #[msg("Signer is not a KYC attester for this mint")]
+    InvalidAttester,
+    #[msg("Too many KYC attesters")]
+    TooManyAttesters,
+    #[msg("Invalid attestation: expiry must be in the future and jurisdiction a two-letter code")]
+    InvalidAttestation,
+    #[msg("Default-frozen accounts need the mint state PDA to hold the freeze authority")]
+    FreezeAuthorityRequired,
+    #[msg("Token-2022 mint was created without the DefaultAccountState extension")]
+    DefaultAccountStateUnsupported,
+    #[msg("Token metadata is immutable")]
+    MetadataImmutable,
+    #[msg("An authority change of this kind is already queued")]
+    AuthorityChangeAlreadyQueued,
+    #[msg("Too many minters are using their rate limit capacity in this window")]
+    RateLimitTableFull,
+    #[msg("The same account was passed more than once")]
+    DuplicateAccount,
+    #[msg("An authority handover of this kind is already proposed")]
+    AuthorityChangeAlreadyProposed,
+    #[msg("Account is not the freeze record's rent payer")]
+    InvalidRecordPayer,
+    #[msg("Attestation was issued by another attester")]
+    AttestationHeldByOtherAttester,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -60,6 +210,10 @@ pub struct TokenMetadata {
     pub symbol: String,
     pub uri: String,
     pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<MetadataCreator>>,
+    pub collection: Option<MetadataCollection>,
+    pub uses: Option<MetadataUses>,
+    pub is_mutable: bool,
 }
 
 impl TokenMetadata {
@@ -68,727 +222,6709 @@ impl TokenMetadata {
         require!(!self.symbol.is_empty(), TokenError::InvalidSymbol);
         require!(self.uri.len() <= 200, TokenError::UriTooLong);
         require!(self.seller_fee_basis_points <= 10000, TokenError::InvalidMintConfig);
+        if let Some(creators) = &self.creators {
+            require!(
+                !creators.is_empty() && creators.len() <= MAX_CREATORS,
+                TokenError::InvalidCreators
+            );
+            let total_share: u16 = creators.iter().map(|c| c.share as u16).sum();
+            require!(total_share == 100, TokenError::InvalidCreators);
+        }
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct InitializeMint<'info> {
-    #[account(
-        init,
-        payer = payer,
-        space = 82,
-        owner = token_program.key(),
-    )]
-    /// CHECK: This will be initialized as a mint account by the token program
-    pub mint: UncheckedAccount<'info>,
+    /// Convert into the Metaplex `DataV2` layout
+    pub fn to_data_v2(&self) -> DataV2 {
+        DataV2 {
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            seller_fee_basis_points: self.seller_fee_basis_points,
+            creators: self
+                .creators
+                .as_ref()
+                .map(|creators| creators.iter().cloned().map(Creator::from).collect()),
+            collection: self.collection.clone().map(Collection::from),
+            uses: self.uses.clone().map(Uses::from),
+        }
+    }
+}
 
-    #[account(mut)]
-    pub payer: Signer<'info>,
+// Mirrors of the Metaplex types so they can be used as instruction arguments
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MetadataCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+impl From<Creator> for MetadataCreator {
+    fn from(creator: Creator) -> Self {
+        MetadataCreator {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
 }
 
-#[derive(Accounts)]
-pub struct CreateTokenWithMetadata<'info> {
-    #[account(
-        init,
-        payer = payer,
-        space = 82,
-        owner = token_program.key(),
-    )]
-    /// CHECK: This will be initialized as a mint account by the token program
-    pub mint: UncheckedAccount<'info>,
+impl From<MetadataCreator> for Creator {
+    fn from(creator: MetadataCreator) -> Self {
+        Creator {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
 
-    /// CHECK: This is the metadata account that will be created by Metaplex
-    #[account(
-        mut,
-        seeds = [
-            "metadata".as_bytes(),
-            token_metadata_program.key().as_ref(),
-            mint.key().as_ref(),
-        ],
-        bump,
-        seeds::program = token_metadata_program.key(),
-    )]
-    pub metadata: UncheckedAccount<'info>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MetadataCollection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
 
-    #[account(mut)]
-    pub payer: Signer<'info>,
+impl From<MetadataCollection> for Collection {
+    fn from(collection: MetadataCollection) -> Self {
+        Collection {
+            verified: collection.verified,
+            key: collection.key,
+        }
+    }
+}
 
-    /// CHECK: This is the update authority for the metadata
-    pub update_authority: UncheckedAccount<'info>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataUseMethod {
+    Burn,
+    Multiple,
+    Single,
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    
-    /// CHECK: This is the Metaplex Token Metadata program
-    pub token_metadata_program: UncheckedAccount<'info>,
-    
-    pub rent: Sysvar<'info, Rent>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MetadataUses {
+    pub use_method: MetadataUseMethod,
+    pub remaining: u64,
+    pub total: u64,
 }
 
-#[derive(Accounts)]
-pub struct MintTokens<'info> {
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
+impl From<MetadataUses> for Uses {
+    fn from(uses: MetadataUses) -> Self {
+        Uses {
+            use_method: match uses.use_method {
+                MetadataUseMethod::Burn => UseMethod::Burn,
+                MetadataUseMethod::Multiple => UseMethod::Multiple,
+                MetadataUseMethod::Single => UseMethod::Single,
+            },
+            remaining: uses.remaining,
+            total: uses.total,
+        }
+    }
+}
 
-    #[account(
-        init_if_needed,
-        payer = mint_authority,
-        associated_token::mint = mint,
-        associated_token::authority = destination_owner,
-    )]
-    pub destination: Account<'info, TokenAccount>,
+// Per-mint program state, created alongside the mint
+#[account]
+pub struct TokenMintState {
+    pub mint: Pubkey,
+    /// The mint authority, or the key allowed to mint through the program when
+    /// this PDA holds the mint authority
+    pub mint_authority: Pubkey,
+    pub total_minted: u64,
+    pub max_supply: u64,
+    /// Seconds an authority change must be queued before it can execute (0 = no timelock)
+    pub authority_delay: i64,
+    pub paused: bool,
+    /// Set once a rate limit PDA is configured; mints must then pass it
+    pub rate_limited: bool,
+    /// Manages the denylist; while set, transfers and mints must pass denylist entries
+    pub compliance_authority: Option<Pubkey>,
+    /// While set, every instruction delivering tokens requires a valid KYC attestation
+    pub kyc_required: bool,
+    /// New token accounts start frozen until the freeze authority thaws them
+    pub default_frozen: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
 
-    /// CHECK: This is the destination token account owner
-    pub destination_owner: UncheckedAccount<'info>,
+impl TokenMintState {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // mint_authority
+        8 +  // total_minted
+        8 +  // max_supply
+        8 +  // authority_delay
+        1 +  // paused
+        1 +  // rate_limited
+        1 + 32 + // compliance_authority
+        1 +  // kyc_required
+        1 +  // default_frozen
+        8 +  // created_at
+        1;   // bump
+
+    pub fn require_not_paused(&self) -> Result<()> {
+        require!(!self.paused, TokenError::Paused);
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub mint_authority: Signer<'info>,
+    /// Authority changes take effect immediately only when no timelock is configured
+    pub fn require_no_timelock(&self) -> Result<()> {
+        require!(self.authority_delay == 0, TokenError::AuthorityChangeTimelocked);
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    /// Raise the authority timelock; lowering it is never allowed
+    pub fn raise_authority_delay(&mut self, new_delay: i64) -> Result<()> {
+        require!(
+            new_delay >= self.authority_delay && new_delay <= MAX_AUTHORITY_DELAY,
+            TokenError::InvalidAuthorityDelay
+        );
+        self.authority_delay = new_delay;
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct BatchMintTokens<'info> {
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
+    /// Check a mint of `amount` against the supply cap and return the new supply
+    pub fn check_supply_cap(&self, current_supply: u64, amount: u64) -> Result<u64> {
+        let new_supply = calculate_total_supply_after_mint(current_supply, amount)?;
+        require!(new_supply <= self.max_supply, TokenError::SupplyCapExceeded);
+        Ok(new_supply)
+    }
 
-    #[account(mut)]
-    pub mint_authority: Signer<'info>,
+    /// Lower the supply cap; raising it is never allowed
+    pub fn lower_max_supply(&mut self, new_max_supply: u64, current_supply: u64) -> Result<()> {
+        require!(
+            new_max_supply <= self.max_supply && new_max_supply >= current_supply,
+            TokenError::InvalidSupplyCap
+        );
+        self.max_supply = new_max_supply;
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
+    pub fn record_mint(&mut self, amount: u64) -> Result<()> {
+        self.total_minted = self
+            .total_minted
+            .checked_add(amount)
+            .ok_or(TokenError::SupplyOverflow)?;
+        Ok(())
+    }
 }
 
-#[derive(Accounts)]
-pub struct TransferTokens<'info> {
-    #[account(mut, constraint = !from.is_frozen() @ TokenError::AccountFrozen)]
-    pub from: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub to: Account<'info, TokenAccount>,
+// M-of-N multisig that can hold the mint and freeze authorities of a mint
+#[account]
+pub struct Multisig {
+    pub mint: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub proposal_count: u64,
+    pub bump: u8,
+}
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+impl Multisig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        4 + 32 * MAX_MULTISIG_SIGNERS + // signers
+        1 +  // threshold
+        8 +  // proposal_count
+        1;   // bump
 
-    pub token_program: Program<'info, Token>,
-}
+    pub fn validate_config(signers: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+            TokenError::InvalidMultisigConfig
+        );
+        require!(
+            threshold > 0 && threshold as usize <= signers.len(),
+            TokenError::InvalidMultisigConfig
+        );
+        for (i, signer) in signers.iter().enumerate() {
+            require!(
+                !signers[..i].contains(signer),
+                TokenError::InvalidMultisigConfig
+            );
+        }
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct GetMintInfo<'info> {
-    pub mint: Account<'info, Mint>,
+    pub fn require_signer(&self, signer: &Pubkey) -> Result<()> {
+        require!(self.signers.contains(signer), TokenError::NotMultisigSigner);
+        Ok(())
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct MintInfo {
-    pub supply: u64,
-    pub decimals: u8,
-    pub mint_authority: Option<Pubkey>,
-    pub freeze_authority: Option<Pubkey>,
+// Operations a multisig proposal can execute
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum MultisigAction {
+    MintTokens { destination: Pubkey, amount: u64 },
+    SetMintAuthority { new_authority: Option<Pubkey> },
+    FreezeAccount { token_account: Pubkey, reason: FreezeReason, case_id: String },
+    ThawAccount { token_account: Pubkey },
+    SetFreezeAuthority { new_authority: Option<Pubkey> },
+    /// Timelocked counterparts of the two actions above, for mints with an authority delay
+    QueueAuthorityChange { kind: AuthorityKind, new_authority: Option<Pubkey> },
+    ExecuteAuthorityChange { kind: AuthorityKind },
+    CancelAuthorityChange { kind: AuthorityKind },
+    /// Two-step handover of an authority the multisig holds, accepted by the new key
+    /// with `accept_authority`
+    ProposeAuthority { kind: AuthorityKind, new_authority: Pubkey },
+    CancelAuthority { kind: AuthorityKind },
 }
 
-#[derive(Accounts)]
-pub struct BurnTokens<'info> {
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
+impl MultisigAction {
+    pub const LEN: usize = 1 + 32 + 1 + 4 + MAX_CASE_ID_LENGTH; // tag + largest variant
 
-    #[account(mut, constraint = !token_account.is_frozen() @ TokenError::AccountFrozen)]
-    pub token_account: Account<'info, TokenAccount>,
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            MultisigAction::MintTokens { amount, .. } => require!(*amount > 0, TokenError::InvalidAmount),
+            MultisigAction::FreezeAccount { case_id, .. } => FreezeRecord::validate_case_id(case_id)?,
+            _ => {}
+        }
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// The token account the action operates on, if any
+    pub fn token_account(&self) -> Option<Pubkey> {
+        match self {
+            MultisigAction::MintTokens { destination, .. } => Some(*destination),
+            MultisigAction::FreezeAccount { token_account, .. }
+            | MultisigAction::ThawAccount { token_account } => Some(*token_account),
+            MultisigAction::SetMintAuthority { .. }
+            | MultisigAction::SetFreezeAuthority { .. }
+            | MultisigAction::QueueAuthorityChange { .. }
+            | MultisigAction::ExecuteAuthorityChange { .. }
+            | MultisigAction::CancelAuthorityChange { .. }
+            | MultisigAction::ProposeAuthority { .. }
+            | MultisigAction::CancelAuthority { .. } => None,
+        }
+    }
+}
 
-    pub token_program: Program<'info, Token>,
+#[account]
+pub struct MultisigProposal {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct SetMintAuthority<'info> {
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
+impl MultisigProposal {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // multisig
+        8 +  // index
+        32 + // proposer
+        MultisigAction::LEN + // action
+        4 + 32 * MAX_MULTISIG_SIGNERS + // approvals
+        1 +  // executed
+        1;   // bump
+
+    /// Record an approval from a multisig member and return the approval count
+    pub fn approve(&mut self, multisig: &Multisig, signer: &Pubkey) -> Result<u8> {
+        multisig.require_signer(signer)?;
+        require!(!self.executed, TokenError::ProposalAlreadyExecuted);
+        require!(!self.approvals.contains(signer), TokenError::AlreadyApproved);
+        self.approvals.push(*signer);
+        Ok(self.approvals.len() as u8)
+    }
 
-    #[account(mut)]
-    pub current_authority: Signer<'info>,
+    pub fn is_approved(&self, multisig: &Multisig) -> bool {
+        self.approvals.len() >= multisig.threshold as usize
+    }
+}
 
-    pub token_program: Program<'info, Token>,
+// Which mint authority a two-step handover moves
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuthorityKind {
+    Mint,
+    Freeze,
 }
 
-#[derive(Accounts)]
-pub struct FreezeAccount<'info> {
-    #[account(mut)]
-    pub token_account: Account<'info, TokenAccount>,
+impl From<AuthorityKind> for AuthorityType {
+    fn from(kind: AuthorityKind) -> Self {
+        match kind {
+            AuthorityKind::Mint => AuthorityType::MintTokens,
+            AuthorityKind::Freeze => AuthorityType::FreezeAccount,
+        }
+    }
+}
 
-    pub mint: Account<'info, Mint>,
+impl AuthorityKind {
+    pub fn seed(&self) -> &'static [u8] {
+        match self {
+            AuthorityKind::Mint => b"mint",
+            AuthorityKind::Freeze => b"freeze",
+        }
+    }
+}
 
-    #[account(mut)]
-    pub freeze_authority: Signer<'info>,
+// A proposed authority handover awaiting acceptance by the new authority.
+// Unless the mint state PDA holds the mint authority, this PDA holds the
+// authority in escrow until the handover is accepted or cancelled.
+#[account]
+pub struct PendingAuthority {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub proposer: Pubkey,
+    pub new_authority: Pubkey,
+    pub escrowed: bool,
+    pub proposed_at: i64,
+    pub bump: u8,
+}
 
-    pub token_program: Program<'info, Token>,
+impl PendingAuthority {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        1 +  // kind
+        32 + // proposer
+        32 + // new_authority
+        1 +  // escrowed
+        8 +  // proposed_at
+        1;   // bump
 }
 
-#[derive(Accounts)]
-pub struct ThawAccount<'info> {
-    #[account(mut)]
-    pub token_account: Account<'info, TokenAccount>,
+// An authority change announced ahead of time on a timelocked mint
+#[account]
+pub struct QueuedAuthorityChange {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub new_authority: Option<Pubkey>,
+    pub queued_by: Pubkey,
+    pub execute_after: i64,
+    pub bump: u8,
+}
 
-    pub mint: Account<'info, Mint>,
+impl QueuedAuthorityChange {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        1 +  // kind
+        1 + 32 + // new_authority
+        32 + // queued_by
+        8 +  // execute_after
+        1;   // bump
+
+    pub fn is_ready(&self, now: i64) -> bool {
+        now >= self.execute_after
+    }
+}
 
-    #[account(mut)]
-    pub freeze_authority: Signer<'info>,
+// A wallet blocked from sending or receiving a mint's tokens. The PDA existing is
+// what blocks it; the fields are for audits.
+#[account]
+pub struct DenylistEntry {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub case_id: String,
+    pub added_by: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+}
 
-    pub token_program: Program<'info, Token>,
+impl DenylistEntry {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // wallet
+        4 + MAX_CASE_ID_LENGTH + // case_id
+        32 + // added_by
+        8 +  // added_at
+        1;   // bump
 }
 
-#[derive(Accounts)]
-pub struct CloseAccount<'info> {
-    #[account(mut, close = destination)]
-    pub token_account: Account<'info, TokenAccount>,
+// KYC attesters allowed to issue attestations for a mint
+#[account]
+pub struct KycConfig {
+    pub mint: Pubkey,
+    pub attesters: Vec<Pubkey>,
+    pub bump: u8,
+}
 
-    /// CHECK: This account will receive the rent
-    #[account(mut)]
-    pub destination: UncheckedAccount<'info>,
+impl KycConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        4 + 32 * MAX_KYC_ATTESTERS + // attesters
+        1;   // bump
 
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    pub fn is_attester(&self, key: &Pubkey) -> bool {
+        self.attesters.contains(key)
+    }
+
+    pub fn add(&mut self, attester: Pubkey) -> Result<()> {
+        if !self.is_attester(&attester) {
+            require!(self.attesters.len() < MAX_KYC_ATTESTERS, TokenError::TooManyAttesters);
+            self.attesters.push(attester);
+        }
+        Ok(())
+    }
 
-    pub token_program: Program<'info, Token>,
+    pub fn remove(&mut self, attester: &Pubkey) -> Result<()> {
+        let index = self
+            .attesters
+            .iter()
+            .position(|key| key == attester)
+            .ok_or(TokenError::InvalidAttester)?;
+        self.attesters.swap_remove(index);
+        Ok(())
+    }
 }
 
-// Events for better monitoring and indexing
-#[event]
-pub struct TokenCreated {
+// A wallet's KYC attestation for a mint. Attestations stay valid until expiry or
+// revocation, even if their attester is later removed.
+#[account]
+pub struct Attestation {
     pub mint: Pubkey,
-    pub authority: Pubkey,
-    pub decimals: u8,
-    pub name: String,
-    pub symbol: String,
+    pub wallet: Pubkey,
+    pub attester: Pubkey,
+    /// ISO 3166-1 alpha-2 country code
+    pub jurisdiction: [u8; 2],
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
 }
 
-#[event]
-pub struct TokensMinted {
-    pub mint: Pubkey,
-    pub recipient: Pubkey,
-    pub amount: u64,
+impl Attestation {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // wallet
+        32 + // attester
+        2 +  // jurisdiction
+        8 +  // issued_at
+        8 +  // expires_at
+        1;   // bump
+
+    pub fn validate(jurisdiction: &[u8; 2], expires_at: i64, now: i64) -> Result<()> {
+        require!(
+            jurisdiction.iter().all(u8::is_ascii_uppercase) && expires_at > now,
+            TokenError::InvalidAttestation
+        );
+        Ok(())
+    }
+
+    pub fn is_valid(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
 }
 
-#[event]
-pub struct TokensTransferred {
-    pub mint: Pubkey,
-    pub from: Pubkey,
-    pub to: Pubkey,
-    pub amount: u64,
+// Why an account was frozen
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FreezeReason {
+    Compliance,
+    Sanctions,
+    Fraud,
+    CourtOrder,
+    Security,
+    Other,
+    /// Frozen on creation because the mint's accounts start frozen
+    DefaultState,
 }
 
-#[event]
-pub struct TokensBurned {
+// Created when a token account is frozen and closed when it is thawed
+#[account]
+pub struct FreezeRecord {
     pub mint: Pubkey,
     pub account: Pubkey,
-    pub amount: u64,
+    pub reason: FreezeReason,
+    /// Free-form reference, e.g. a support or compliance case number
+    pub case_id: String,
+    pub frozen_at: i64,
+    pub frozen_by: Pubkey,
+    /// Paid the record's rent, and gets it back when the record is closed
+    pub payer: Pubkey,
+    pub bump: u8,
 }
 
-#[event]
-pub struct AccountFrozen {
-    pub mint: Pubkey,
-    pub account: Pubkey,
+impl FreezeRecord {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // account
+        1 +  // reason
+        4 + MAX_CASE_ID_LENGTH + // case_id
+        8 +  // frozen_at
+        32 + // frozen_by
+        32 + // payer
+        1;   // bump
+
+    pub fn validate_case_id(case_id: &str) -> Result<()> {
+        require!(case_id.len() <= MAX_CASE_ID_LENGTH, TokenError::CaseIdTooLong);
+        Ok(())
+    }
 }
 
-#[event]
-pub struct AccountThawed {
-    pub mint: Pubkey,
-    pub account: Pubkey,
+// Roles that can be granted through a mint's RBAC PDA
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Minter,
+    Freezer,
+    Pauser,
+    Admin,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct MintDestination {
-    pub recipient: Pubkey,
-    pub amount: u64,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RoleGrant {
+    pub holder: Pubkey,
+    pub role: Role,
+    /// Remaining base units for minters, or remaining operations for other roles
+    /// (None = unlimited)
+    pub allowance: Option<u64>,
 }
 
-#[program]
-pub mod spl_token_mint {
-    use super::*;
+impl RoleGrant {
+    pub const LEN: usize = 32 + 1 + 1 + 8;
+}
 
-    /// Initialize a new mint with specified parameters (basic version without metadata)
-    pub fn initialize_mint(
-        ctx: Context<InitializeMint>,
-        decimals: u8,
-        mint_authority: Pubkey,
-        freeze_authority: Option<Pubkey>,
-    ) -> Result<()> {
-        // Validate decimals
-        require!(decimals <= 9, TokenError::InvalidDecimals);
+// Per-mint role registry. Role holders act through the program, so minting and
+// freezing by role require the mint state PDA to hold the matching authority.
+#[account]
+pub struct Roles {
+    pub mint: Pubkey,
+    pub grants: Vec<RoleGrant>,
+    pub bump: u8,
+}
 
-        // Initialize the mint using CPI to the token program
-        let cpi_accounts = anchor_spl::token::InitializeMint {
-            mint: ctx.accounts.mint.to_account_info(),
-            rent: ctx.accounts.rent.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+impl Roles {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        4 + RoleGrant::LEN * MAX_ROLE_GRANTS + // grants
+        1;   // bump
 
-        anchor_spl::token::initialize_mint(cpi_ctx, decimals, &mint_authority, freeze_authority.as_ref())?;
+    pub fn has_role(&self, holder: &Pubkey, role: Role) -> bool {
+        self.grants
+            .iter()
+            .any(|grant| grant.holder == *holder && grant.role == role)
+    }
 
-        msg!("Token mint initialized successfully!");
-        msg!("Mint address: {}", ctx.accounts.mint.key());
-        msg!("Mint authority: {}", mint_authority);
-        if let Some(freeze_auth) = freeze_authority {
-            msg!("Freeze authority: {}", freeze_auth);
-        } else {
-            msg!("Freeze authority: None");
+    /// Grant a role, replacing the allowance if the holder already has it
+    pub fn grant(&mut self, holder: Pubkey, role: Role, allowance: Option<u64>) -> Result<()> {
+        if let Some(grant) = self
+            .grants
+            .iter_mut()
+            .find(|grant| grant.holder == holder && grant.role == role)
+        {
+            grant.allowance = allowance;
+            return Ok(());
         }
-        msg!("Decimals: {}", decimals);
-        
+
+        require!(self.grants.len() < MAX_ROLE_GRANTS, TokenError::RoleGrantLimitExceeded);
+        self.grants.push(RoleGrant { holder, role, allowance });
         Ok(())
     }
 
-    /// Create a new token with metadata (name, symbol, logo URI)
-    /// This function creates the mint and prepares for metadata creation
-    pub fn create_token_with_metadata(
-        ctx: Context<CreateTokenWithMetadata>,
-        decimals: u8,
-        metadata: TokenMetadata,
-        mint_authority: Pubkey,
-        freeze_authority: Option<Pubkey>,
-    ) -> Result<()> {
-        // Validate inputs
-        require!(decimals <= 9, TokenError::InvalidDecimals);
-        metadata.validate()?;
-
-        // First, initialize the mint
-        let cpi_accounts = anchor_spl::token::InitializeMint {
-            mint: ctx.accounts.mint.to_account_info(),
-            rent: ctx.accounts.rent.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-
-        anchor_spl::token::initialize_mint(cpi_ctx, decimals, &mint_authority, freeze_authority.as_ref())?;
-
-        // Create metadata using raw instruction
-        let metadata_instruction_data = create_metadata_instruction_data(
-            ctx.accounts.metadata.key(),
-            ctx.accounts.mint.key(),
-            ctx.accounts.payer.key(),
-            ctx.accounts.payer.key(),
-            ctx.accounts.update_authority.key(),
-            metadata.name.clone(),
-            metadata.symbol.clone(),
-            metadata.uri.clone(),
-            metadata.seller_fee_basis_points,
-        );
-
-        let accounts = vec![
-            ctx.accounts.metadata.to_account_info(),
-            ctx.accounts.mint.to_account_info(),
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.update_authority.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.rent.to_account_info(),
-        ];
-
-        let create_metadata_ix = anchor_lang::solana_program::instruction::Instruction {
-            program_id: ctx.accounts.token_metadata_program.key(),
-            accounts: vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(ctx.accounts.metadata.key(), false),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(ctx.accounts.payer.key(), true),
-                anchor_lang::solana_program::instruction::AccountMeta::new(ctx.accounts.payer.key(), true),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(ctx.accounts.update_authority.key(), false),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
-            ],
-            data: metadata_instruction_data,
-        };
-
-        anchor_lang::solana_program::program::invoke(
-            &create_metadata_ix,
-            &accounts,
-        )?;
-
-        // Emit event
-        emit!(TokenCreated {
-            mint: ctx.accounts.mint.key(),
-            authority: mint_authority,
-            decimals,
-            name: metadata.name.clone(),
-            symbol: metadata.symbol.clone(),
-        });
-
-        msg!("Token created successfully!");
-        msg!("Mint address: {}", ctx.accounts.mint.key());
-        msg!("Metadata address: {}", ctx.accounts.metadata.key());
-        msg!("Name: {}", metadata.name);
-        msg!("Symbol: {}", metadata.symbol);
-        msg!("URI: {}", metadata.uri);
-        msg!("Decimals: {}", decimals);
-
+    pub fn revoke(&mut self, holder: &Pubkey, role: Role) -> Result<()> {
+        let index = self
+            .grants
+            .iter()
+            .position(|grant| grant.holder == *holder && grant.role == role)
+            .ok_or(TokenError::RoleNotGranted)?;
+        if role == Role::Admin {
+            let admins = self.grants.iter().filter(|grant| grant.role == Role::Admin).count();
+            require!(admins > 1, TokenError::LastAdminRole);
+        }
+        self.grants.remove(index);
         Ok(())
     }
 
-    /// Mint tokens to a destination account
-    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
-        // Validate amount is not zero
-        require!(amount > 0, TokenError::InvalidAmount);
+    /// Check that `holder` has `role` and spend `amount` of its allowance
+    pub fn consume(&mut self, holder: &Pubkey, role: Role, amount: u64) -> Result<()> {
+        let grant = self
+            .grants
+            .iter_mut()
+            .find(|grant| grant.holder == *holder && grant.role == role)
+            .ok_or(TokenError::MissingRole)?;
+        if let Some(allowance) = grant.allowance {
+            grant.allowance = Some(
+                allowance
+                    .checked_sub(amount)
+                    .ok_or(TokenError::RoleAllowanceExceeded)?,
+            );
+        }
+        Ok(())
+    }
+}
 
-        // Verify mint authority
-        require!(
-            ctx.accounts.mint.mint_authority.is_some(),
-            TokenError::Unauthorized
-        );
-        require!(
-            ctx.accounts.mint.mint_authority.unwrap() == ctx.accounts.mint_authority.key(),
-            TokenError::Unauthorized
-        );
+// Minting capacity that refills linearly, reaching the full limit over one window.
+// Capacity is kept in base units times the window length, so every second refills
+// exactly `limit` scaled units and no partial refill is lost between mints.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RateBucket {
+    pub available_scaled: u128,
+    pub updated_at: i64,
+}
 
-        // Check for potential overflow
-        let current_supply = ctx.accounts.mint.supply;
-        require!(
-            current_supply.checked_add(amount).is_some(),
-            TokenError::SupplyOverflow
-        );
+impl RateBucket {
+    pub const LEN: usize = 16 + 8;
 
-        // Mint tokens
-        let cpi_accounts = SplMintTo {
-            mint: ctx.accounts.mint.to_account_info(),
-            to: ctx.accounts.destination.to_account_info(),
-            authority: ctx.accounts.mint_authority.to_account_info(),
-        };
+    pub fn full(limit: u64, window_seconds: i64, now: i64) -> Self {
+        Self {
+            available_scaled: limit as u128 * window_seconds as u128,
+            updated_at: now,
+        }
+    }
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    fn scaled_at(&self, limit: u64, window_seconds: i64, now: i64) -> u128 {
+        let elapsed = now.saturating_sub(self.updated_at).max(0) as u128;
+        self.available_scaled
+            .saturating_add(elapsed * limit as u128)
+            .min(limit as u128 * window_seconds as u128)
+    }
 
-        token::mint_to(cpi_ctx, amount)?;
+    /// Capacity available at `now` given the window limit
+    pub fn available_at(&self, limit: u64, window_seconds: i64, now: i64) -> u64 {
+        (self.scaled_at(limit, window_seconds, now) / window_seconds as u128) as u64
+    }
 
-        // Emit event
-        emit!(TokensMinted {
-            mint: ctx.accounts.mint.key(),
-            recipient: ctx.accounts.destination.key(),
-            amount,
-        });
+    /// Carry the capacity left at `now` over to a new limit and window
+    pub fn rescale(&mut self, limit: u64, window_seconds: i64, new_limit: u64, new_window_seconds: i64, now: i64) {
+        let available = self.available_at(limit, window_seconds, now).min(new_limit);
+        self.available_scaled = available as u128 * new_window_seconds as u128;
+        self.updated_at = now;
+    }
 
-        msg!("Minted {} tokens to {}", amount, ctx.accounts.destination.key());
-        
+    pub fn consume(&mut self, amount: u64, limit: u64, window_seconds: i64, now: i64) -> Result<()> {
+        let available = self.scaled_at(limit, window_seconds, now);
+        let cost = amount as u128 * window_seconds as u128;
+        require!(cost <= available, TokenError::RateLimitExceeded);
+        self.available_scaled = available - cost;
+        self.updated_at = now;
         Ok(())
     }
+}
 
-    /// Batch mint tokens to multiple destinations (limited to 10 for safety)
-    pub fn batch_mint_tokens(
-        ctx: Context<BatchMintTokens>,
-        destinations: Vec<MintDestination>,
-    ) -> Result<()> {
-        // Validate batch size
-        require!(destinations.len() <= 10, TokenError::BatchLimitExceeded);
-        require!(!destinations.is_empty(), TokenError::InvalidAmount);
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct MinterBucket {
+    pub minter: Pubkey,
+    pub bucket: RateBucket,
+}
 
-        // Verify mint authority
-        require!(
-            ctx.accounts.mint.mint_authority.is_some(),
-            TokenError::Unauthorized
-        );
-        require!(
-            ctx.accounts.mint.mint_authority.unwrap() == ctx.accounts.mint_authority.key(),
-            TokenError::Unauthorized
-        );
+impl MinterBucket {
+    pub const LEN: usize = 32 + RateBucket::LEN;
+}
 
-        // Calculate total amount and check for overflow
-        let total_amount: u64 = destinations.iter().map(|d| d.amount).sum();
-        let current_supply = ctx.accounts.mint.supply;
+// Rolling-window mint limits for a mint, overall and per minting key
+#[account]
+pub struct RateLimit {
+    pub mint: Pubkey,
+    pub window_seconds: i64,
+    pub mint_limit: u64,
+    pub minter_limit: u64,
+    pub mint_bucket: RateBucket,
+    pub minters: Vec<MinterBucket>,
+    pub bump: u8,
+}
+
+impl RateLimit {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        8 +  // window_seconds
+        8 +  // mint_limit
+        8 +  // minter_limit
+        RateBucket::LEN + // mint_bucket
+        4 + MinterBucket::LEN * MAX_RATE_LIMITED_MINTERS + // minters
+        1;   // bump
+
+    pub fn validate_config(window_seconds: i64, mint_limit: u64, minter_limit: u64) -> Result<()> {
         require!(
-            current_supply.checked_add(total_amount).is_some(),
-            TokenError::SupplyOverflow
+            window_seconds > 0 && mint_limit > 0 && minter_limit > 0,
+            TokenError::InvalidRateLimit
         );
-
-        // This is a foundation for batch minting - in practice, you'd need to handle
-        // the token accounts within the instruction or use remaining_accounts
-        msg!("Batch mint prepared for {} destinations, total amount: {}", 
-             destinations.len(), total_amount);
-
         Ok(())
     }
 
-    /// Transfer tokens between accounts
-    pub fn transfer_tokens(ctx: Context<TransferTokens>, amount: u64) -> Result<()> {
-        // Validate amount
-        require!(amount > 0, TokenError::InvalidAmount);
-
-        // Check sufficient balance
+    /// Tighten a configured limit: limits can only be lowered and the window only
+    /// lengthened, and capacity already used stays used
+    pub fn tighten(&mut self, window_seconds: i64, mint_limit: u64, minter_limit: u64, now: i64) -> Result<()> {
+        Self::validate_config(window_seconds, mint_limit, minter_limit)?;
         require!(
-            ctx.accounts.from.amount >= amount,
-            TokenError::InsufficientFunds
+            window_seconds >= self.window_seconds
+                && mint_limit <= self.mint_limit
+                && minter_limit <= self.minter_limit,
+            TokenError::InvalidRateLimit
         );
 
-        // Verify authority owns the source account
-        require!(
-            ctx.accounts.from.owner == ctx.accounts.authority.key(),
-            TokenError::InvalidOwner
-        );
+        let (old_window, old_minter_limit) = (self.window_seconds, self.minter_limit);
+        self.mint_bucket
+            .rescale(self.mint_limit, old_window, mint_limit, window_seconds, now);
+        for entry in self.minters.iter_mut() {
+            entry
+                .bucket
+                .rescale(old_minter_limit, old_window, minter_limit, window_seconds, now);
+        }
+        self.window_seconds = window_seconds;
+        self.mint_limit = mint_limit;
+        self.minter_limit = minter_limit;
+        Ok(())
+    }
 
-        // Verify both accounts have the same mint
-        require!(
-            ctx.accounts.from.mint == ctx.accounts.to.mint,
-            TokenError::MintMismatch
-        );
+    /// Remaining (mint, minter) capacity at `now`
+    pub fn remaining(&self, minter: &Pubkey, now: i64) -> (u64, u64) {
+        let mint_remaining = self
+            .mint_bucket
+            .available_at(self.mint_limit, self.window_seconds, now);
+        let minter_remaining = self
+            .minters
+            .iter()
+            .find(|entry| entry.minter == *minter)
+            .map_or(self.minter_limit, |entry| {
+                entry.bucket.available_at(self.minter_limit, self.window_seconds, now)
+            });
+        (mint_remaining, minter_remaining)
+    }
 
-        // Additional security: prevent self-transfer
+    /// Spend `amount` of both the mint's and the minter's capacity
+    pub fn consume(&mut self, minter: &Pubkey, amount: u64, now: i64) -> Result<()> {
+        let (limit, window) = (self.minter_limit, self.window_seconds);
+        let (mint_remaining, minter_remaining) = self.remaining(minter, now);
         require!(
-            ctx.accounts.from.key() != ctx.accounts.to.key(),
-            TokenError::InvalidTransfer
+            amount <= mint_remaining && amount <= minter_remaining,
+            TokenError::RateLimitExceeded
         );
+        self.mint_bucket.consume(amount, self.mint_limit, window, now)?;
 
-        // Perform transfer
-        let cpi_accounts = SplTransfer {
-            from: ctx.accounts.from.to_account_info(),
-            to: ctx.accounts.to.to_account_info(),
-            authority: ctx.accounts.authority.to_account_info(),
+        let index = match self.minters.iter().position(|entry| entry.minter == *minter) {
+            Some(index) => index,
+            None => {
+                // Minters back at full capacity carry no state, so make room by dropping them
+                if self.minters.len() >= MAX_RATE_LIMITED_MINTERS {
+                    self.minters
+                        .retain(|entry| entry.bucket.available_at(limit, window, now) < limit);
+                }
+                require!(
+                    self.minters.len() < MAX_RATE_LIMITED_MINTERS,
+                    TokenError::RateLimitTableFull
+                );
+                self.minters.push(MinterBucket {
+                    minter: *minter,
+                    bucket: RateBucket::full(limit, window, now),
+                });
+                self.minters.len() - 1
+            }
         };
+        self.minters[index].bucket.consume(amount, limit, window, now)
+    }
+}
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+// Merkle airdrop for a mint. Tokens are escrowed in the distributor's ATA and
+// each leaf commits to (index, claimant, amount); `claimed` is a bitmap over index.
+#[account]
+pub struct Distributor {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_allocation: u64,
+    pub total_claimed: u64,
+    pub num_recipients: u32,
+    pub expires_at: i64,
+    pub bump: u8,
+    pub claimed: Vec<u8>,
+}
 
-        token::transfer(cpi_ctx, amount)?;
+impl Distributor {
+    pub fn space(num_recipients: u32) -> usize {
+        8 +  // discriminator
+        32 + // mint
+        32 + // authority
+        32 + // merkle_root
+        8 +  // total_allocation
+        8 +  // total_claimed
+        4 +  // num_recipients
+        8 +  // expires_at
+        1 +  // bump
+        4 + Self::bitmap_len(num_recipients) // claimed
+    }
 
-        // Emit event
-        emit!(TokensTransferred {
-            mint: ctx.accounts.from.mint,
-            from: ctx.accounts.from.key(),
-            to: ctx.accounts.to.key(),
-            amount,
-        });
+    pub fn bitmap_len(num_recipients: u32) -> usize {
+        (num_recipients as usize).div_ceil(8)
+    }
 
-        msg!(
-            "Transferred {} tokens from {} to {}",
-            amount,
-            ctx.accounts.from.key(),
-            ctx.accounts.to.key()
+    pub fn validate_config(total_allocation: u64, num_recipients: u32, expires_at: i64, now: i64) -> Result<()> {
+        require!(
+            total_allocation > 0
+                && num_recipients > 0
+                && num_recipients as usize <= MAX_AIRDROP_RECIPIENTS
+                && expires_at > now,
+            TokenError::InvalidAirdropConfig
         );
-
         Ok(())
     }
 
-    /// Get mint information
-    pub fn get_mint_info(ctx: Context<GetMintInfo>) -> Result<MintInfo> {
-        let mint = &ctx.accounts.mint;
-        
-        Ok(MintInfo {
-            supply: mint.supply,
-            decimals: mint.decimals,
-            mint_authority: mint.mint_authority.into(),
-            freeze_authority: mint.freeze_authority.into(),
-        })
+    pub fn is_claimed(&self, index: u64) -> bool {
+        index < self.num_recipients as u64 && self.claimed[(index / 8) as usize] & (1 << (index % 8)) != 0
     }
 
-    /// Burn tokens from an account
-    pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
-        // Validate amount
-        require!(amount > 0, TokenError::InvalidAmount);
-
-        // Check sufficient balance
+    /// Verify a claim against the merkle root and mark it in the bitmap
+    pub fn claim(&mut self, index: u64, claimant: &Pubkey, amount: u64, proof: &[[u8; 32]]) -> Result<()> {
+        require!(index < self.num_recipients as u64, TokenError::InvalidMerkleProof);
+        require!(!self.is_claimed(index), TokenError::AlreadyClaimed);
         require!(
-            ctx.accounts.token_account.amount >= amount,
-            TokenError::BurnAmountExceedsBalance
+            verify_merkle_proof(proof, &self.merkle_root, airdrop_leaf(index, claimant, amount)),
+            TokenError::InvalidMerkleProof
         );
 
-        // Verify authority owns the token account
-        require!(
-            ctx.accounts.token_account.owner == ctx.accounts.authority.key(),
-            TokenError::InvalidOwner
-        );
+        let total_claimed = self
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(TokenError::SupplyOverflow)?;
+        require!(total_claimed <= self.total_allocation, TokenError::InsufficientFunds);
 
-        // Verify the token account belongs to the correct mint
+        self.total_claimed = total_claimed;
+        self.claimed[(index / 8) as usize] |= 1 << (index % 8);
+        Ok(())
+    }
+}
+
+// Vesting escrow for one beneficiary. Tokens sit in the vesting PDA's ATA and
+// unlock linearly from `start_ts` to `end_ts`, with nothing before `cliff_ts`.
+#[account]
+pub struct Vesting {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub released: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+    pub revocable: bool,
+    pub bump: u8,
+}
+
+impl Vesting {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // authority
+        32 + // beneficiary
+        8 +  // total_amount
+        8 +  // released
+        8 +  // start_ts
+        8 +  // cliff_ts
+        8 +  // end_ts
+        1 +  // revocable
+        1;   // bump
+
+    pub fn validate_schedule(total_amount: u64, start_ts: i64, cliff_ts: i64, end_ts: i64) -> Result<()> {
         require!(
-            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
-            TokenError::MintMismatch
+            total_amount > 0 && start_ts <= cliff_ts && cliff_ts <= end_ts && start_ts < end_ts,
+            TokenError::InvalidVestingSchedule
         );
+        Ok(())
+    }
 
-        // Burn tokens
-        let cpi_accounts = SplBurn {
-            mint: ctx.accounts.mint.to_account_info(),
-            from: ctx.accounts.token_account.to_account_info(),
-            authority: ctx.accounts.authority.to_account_info(),
-        };
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if now < self.cliff_ts {
+            return 0;
+        }
+        if now >= self.end_ts {
+            return self.total_amount;
+        }
+        let elapsed = (now - self.start_ts) as u128;
+        let duration = (self.end_ts - self.start_ts) as u128;
+        (self.total_amount as u128 * elapsed / duration) as u64
+    }
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    /// Mark the vested but unreleased amount as released and return it
+    pub fn release(&mut self, now: i64) -> Result<u64> {
+        let amount = self.vested_amount(now).saturating_sub(self.released);
+        require!(amount > 0, TokenError::NothingToRelease);
+        self.released += amount;
+        Ok(amount)
+    }
 
-        token::burn(cpi_ctx, amount)?;
+    /// Stop vesting at `now`, capping the total at what has vested, and return
+    /// the unvested amount
+    pub fn revoke(&mut self, now: i64) -> Result<u64> {
+        require!(self.revocable, TokenError::VestingNotRevocable);
+        let vested = self.vested_amount(now);
+        let unvested = self.total_amount - vested;
+        self.total_amount = vested;
+        self.end_ts = self.end_ts.min(now.max(self.cliff_ts));
+        self.revocable = false;
+        Ok(unvested)
+    }
+}
 
-        // Emit event
-        emit!(TokensBurned {
-            mint: ctx.accounts.mint.key(),
-            account: ctx.accounts.token_account.key(),
-            amount,
-        });
+// Sale price as a function of how many tokens the sale has sold. Prices are in
+// lamports per whole token (10^decimals base units).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PriceCurve {
+    Fixed { price: u64 },
+    /// Price rises by `slope` lamports for every whole token sold
+    Linear { base_price: u64, slope: u64 },
+    /// Each whole token costs `growth_bps` basis points more than the one before
+    Exponential { base_price: u64, growth_bps: u32 },
+}
 
-        msg!("Burned {} tokens from {}", amount, ctx.accounts.token_account.key());
+impl PriceCurve {
+    pub const LEN: usize = 1 + 8 + 8; // tag + largest variant
 
+    pub fn validate(&self) -> Result<()> {
+        let valid = match *self {
+            PriceCurve::Fixed { price } => price > 0,
+            PriceCurve::Linear { base_price, slope } => base_price > 0 || slope > 0,
+            PriceCurve::Exponential { base_price, growth_bps } => base_price > 0 && growth_bps > 0,
+        };
+        require!(valid, TokenError::InvalidSaleConfig);
         Ok(())
     }
 
-    /// Set mint authority (can be used to revoke mint authority by setting to None)
-    pub fn set_mint_authority(
-        ctx: Context<SetMintAuthority>,
-        new_authority: Option<Pubkey>,
-    ) -> Result<()> {
-        // Verify current authority
-        require!(
-            ctx.accounts.mint.mint_authority.is_some(),
-            TokenError::Unauthorized
-        );
-        
-        require!(
-            ctx.accounts.mint.mint_authority.unwrap() == ctx.accounts.current_authority.key(),
-            TokenError::Unauthorized
-        );
+    /// Bonding curves price by sold supply, so tokens can be sold back along them
+    pub fn is_bonding(&self) -> bool {
+        !matches!(self, PriceCurve::Fixed { .. })
+    }
 
-        // Set new mint authority (can be None to revoke mint authority permanently)
-        let cpi_accounts = SplSetAuthority {
-            account_or_mint: ctx.accounts.mint.to_account_info(),
-            current_authority: ctx.accounts.current_authority.to_account_info(),
+    /// Lamports paid for the first `sold` base units. Costs are differences of this
+    /// function, so buying and selling back the same tokens nets to zero.
+    pub fn reserve(&self, sold: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.pow(decimals as u32);
+        let sold = sold as u128;
+
+        let reserve = match *self {
+            PriceCurve::Fixed { price } => price as u128 * sold / unit,
+            PriceCurve::Linear { base_price, slope } => {
+                // Integral of base + slope * s over whole tokens s
+                let linear = (base_price as u128)
+                    .checked_mul(sold)
+                    .and_then(|v| v.checked_mul(2 * unit));
+                let quadratic = sold
+                    .checked_mul(sold)
+                    .and_then(|v| v.checked_mul(slope as u128));
+                linear
+                    .zip(quadratic)
+                    .and_then(|(l, q)| l.checked_add(q))
+                    .map(|total| total / (2 * unit * unit))
+                    .ok_or(TokenError::PriceOverflow)?
+            }
+            PriceCurve::Exponential { base_price, growth_bps } => {
+                // Whole token k costs base * r^k; a partial token is priced at its step
+                let ratio = PRICE_SCALE + growth_bps as u128 * PRICE_SCALE / BPS_DENOMINATOR;
+                let (whole, partial) = (sold / unit, sold % unit);
+                let step = pow_scaled(ratio, whole as u64).ok_or(TokenError::PriceOverflow)?;
+                let geometric = (step - PRICE_SCALE)
+                    .checked_mul(PRICE_SCALE)
+                    .map(|v| v / (ratio - PRICE_SCALE));
+                geometric
+                    .zip(step.checked_mul(partial).map(|v| v / unit))
+                    .and_then(|(g, p)| g.checked_add(p))
+                    .and_then(|scaled| scaled.checked_mul(base_price as u128))
+                    .map(|total| total / PRICE_SCALE)
+                    .ok_or(TokenError::PriceOverflow)?
+            }
         };
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        u64::try_from(reserve).map_err(|_| error!(TokenError::PriceOverflow))
+    }
+}
+
+// Public sale of freshly minted tokens, paid in SOL into the treasury PDA
+#[account]
+pub struct Sale {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub curve: PriceCurve,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    /// Maximum base units each wallet may buy (u64::MAX = no cap)
+    pub wallet_cap: u64,
+    /// Base units currently sold through the sale, net of sell-backs
+    pub sold: u64,
+    pub sell_back: bool,
+    pub treasury_bump: u8,
+    pub bump: u8,
+}
 
-        token::set_authority(cpi_ctx, AuthorityType::MintTokens, new_authority)?;
+impl Sale {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // authority
+        PriceCurve::LEN + // curve
+        8 +  // start_ts
+        8 +  // end_ts
+        8 +  // wallet_cap
+        8 +  // sold
+        1 +  // sell_back
+        1 +  // treasury_bump
+        1;   // bump
+
+    pub fn require_active(&self, now: i64) -> Result<()> {
+        require!(now >= self.start_ts && now < self.end_ts, TokenError::SaleNotActive);
+        Ok(())
+    }
 
-        match new_authority {
-            Some(authority) => {
-                msg!("Mint authority changed to: {}", authority);
-            }
-            None => {
-                msg!("Mint authority revoked permanently");
-            }
+    /// Lamports the treasury must keep to refund every outstanding token on sell-back.
+    /// Sell-back closes with the sale, so nothing is reserved once it has ended.
+    pub fn sell_back_reserve(&self, decimals: u8, now: i64) -> Result<u64> {
+        if !self.sell_back || now >= self.end_ts {
+            return Ok(0);
         }
+        self.curve.reserve(self.sold, decimals)
+    }
+}
+
+// Base units a wallet has bought from a sale
+#[account]
+pub struct SalePurchase {
+    pub sale: Pubkey,
+    pub buyer: Pubkey,
+    pub purchased: u64,
+    pub bump: u8,
+}
+
+impl SalePurchase {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // sale
+        32 + // buyer
+        8 +  // purchased
+        1;   // bump
+
+    pub fn record(&mut self, amount: u64, wallet_cap: u64) -> Result<()> {
+        let purchased = self
+            .purchased
+            .checked_add(amount)
+            .ok_or(TokenError::WalletCapExceeded)?;
+        require!(purchased <= wallet_cap, TokenError::WalletCapExceeded);
+        self.purchased = purchased;
+        Ok(())
+    }
+}
+
+// Allowlisted presale: wallets prove a (wallet, max_amount) leaf of the merkle root
+#[account]
+pub struct Presale {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    /// Lamports per base unit
+    pub price: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub total_sold: u64,
+    pub treasury_bump: u8,
+    pub bump: u8,
+}
+
+impl Presale {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // authority
+        32 + // merkle_root
+        8 +  // price
+        8 +  // start_ts
+        8 +  // end_ts
+        8 +  // total_sold
+        1 +  // treasury_bump
+        1;   // bump
+
+    pub fn require_active(&self, now: i64) -> Result<()> {
+        require!(now >= self.start_ts && now < self.end_ts, TokenError::SaleNotActive);
+        Ok(())
+    }
+
+    pub fn verify_allowance(&self, wallet: &Pubkey, max_amount: u64, proof: &[[u8; 32]]) -> Result<()> {
+        let leaf = allowlist_leaf(wallet, max_amount);
+        require!(verify_merkle_proof(proof, &self.merkle_root, leaf), TokenError::InvalidMerkleProof);
+        Ok(())
+    }
+
+    pub fn cost(&self, amount: u64) -> Result<u64> {
+        Ok(amount.checked_mul(self.price).ok_or(TokenError::PriceOverflow)?)
+    }
+}
+
+// Test-token faucet: any wallet may mint `drip_amount` once per cooldown
+#[account]
+pub struct Faucet {
+    pub mint: Pubkey,
+    pub drip_amount: u64,
+    pub cooldown_seconds: i64,
+    pub enabled: bool,
+    pub bump: u8,
+}
+
+impl Faucet {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        8 +  // drip_amount
+        8 +  // cooldown_seconds
+        1 +  // enabled
+        1;   // bump
+
+    /// Record a drip for `claim` at `now`, returning when the wallet may drip again
+    pub fn drip(&self, claim: &mut FaucetClaim, now: i64) -> Result<i64> {
+        require!(self.enabled, TokenError::FaucetDisabled);
+        if claim.last_drip_at.is_some_and(|last| now < last.saturating_add(self.cooldown_seconds)) {
+            return err!(TokenError::FaucetCooldown);
+        }
+
+        claim.last_drip_at = Some(now);
+        Ok(now.saturating_add(self.cooldown_seconds))
+    }
+}
+
+// Last faucet drip for one wallet
+#[account]
+pub struct FaucetClaim {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub last_drip_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl FaucetClaim {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        32 + // wallet
+        1 + 8 + // last_drip_at
+        1;   // bump
+}
+
+#[derive(Accounts)]
+#[instruction(
+    decimals: u8,
+    mint_authority: Pubkey,
+    freeze_authority: Option<Pubkey>,
+    max_supply: Option<u64>,
+    program_mint_authority: bool,
+    default_frozen: bool,
+)]
+pub struct InitializeMint<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = mint_account_space(&token_program.key(), default_frozen)?,
+        owner = token_program.key(),
+    )]
+    /// CHECK: This will be initialized as a mint account by the token program
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = TokenMintState::LEN,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTokenWithMetadata<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 82,
+        owner = token_program.key(),
+    )]
+    /// CHECK: This will be initialized as a mint account by the token program
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = TokenMintState::LEN,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// CHECK: This is the metadata account that will be created by Metaplex
+    #[account(
+        mut,
+        seeds = [
+            "metadata".as_bytes(),
+            token_metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: This is the update authority for the metadata
+    pub update_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    /// CHECK: This is the Metaplex Token Metadata program
+    #[account(address = mpl_token_metadata::ID @ TokenError::ProgramAccountMismatch)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTokenMetadata<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: This is the Metaplex metadata account, decoded in the handler
+    #[account(
+        mut,
+        seeds = [
+            "metadata".as_bytes(),
+            token_metadata_program.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+        owner = token_metadata_program.key() @ TokenError::InvalidMetadataAccount,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    pub update_authority: Signer<'info>,
+
+    /// CHECK: This is the Metaplex Token Metadata program
+    #[account(address = mpl_token_metadata::ID @ TokenError::ProgramAccountMismatch)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintTokens<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// Required when minting as a holder of the minter role
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    /// Required once a rate limit is configured for the mint
+    #[account(
+        mut,
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump = rate_limit.bump,
+    )]
+    pub rate_limit: Option<Account<'info, RateLimit>>,
+
+    /// CHECK: The destination owner's ATA, created in the handler if it does not exist yet
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&destination_owner.key(), &mint.key(), &token_program.key())
+            @ TokenError::InvalidAssociatedTokenAccount,
+    )]
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: This is the destination token account owner
+    pub destination_owner: UncheckedAccount<'info>,
+
+    /// CHECK: Denylist PDA of the destination owner; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), destination_owner.key().as_ref()], bump)]
+    pub destination_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the destination; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), destination_owner.key().as_ref()], bump)]
+    pub destination_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Freeze record PDA of the destination; required when a default-frozen mint creates it
+    #[account(mut, seeds = [FREEZE_RECORD_SEED, destination.key().as_ref()], bump)]
+    pub destination_freeze_record: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub mint_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct BatchMintTokens<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// Required once a rate limit is configured for the mint
+    #[account(
+        mut,
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump = rate_limit.bump,
+    )]
+    pub rate_limit: Option<Account<'info, RateLimit>>,
+
+    #[account(mut)]
+    pub mint_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct BatchTransferTokens<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(mut, constraint = !from.is_frozen() @ TokenError::AccountFrozen)]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Denylist PDA of the sender; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), from.owner.as_ref()], bump)]
+    pub sender_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the signing owner or delegate; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), authority.key().as_ref()], bump)]
+    pub authority_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct TransferTokens<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(mut, constraint = !from.is_frozen() @ TokenError::AccountFrozen)]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Denylist PDA of the sender; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), from.owner.as_ref()], bump)]
+    pub sender_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the recipient; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), to.owner.as_ref()], bump)]
+    pub recipient_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the recipient; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), to.owner.as_ref()], bump)]
+    pub recipient_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the signing owner or delegate; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), authority.key().as_ref()], bump)]
+    pub authority_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct GetMintInfo<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRateLimit<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init_if_needed,
+        payer = mint_authority,
+        space = RateLimit::LEN,
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub rate_limit: Account<'info, RateLimit>,
+
+    #[account(mut)]
+    pub mint_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetMintCapacity<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump = rate_limit.bump,
+    )]
+    pub rate_limit: Account<'info, RateLimit>,
+}
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32], total_allocation: u64, num_recipients: u32)]
+pub struct CreateDistributor<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Distributor::space(num_recipients),
+        seeds = [DISTRIBUTOR_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub distributor: Account<'info, Distributor>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = distributor,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Funds the airdrop with `total_allocation` tokens
+    #[account(mut)]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAirdrop<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        seeds = [DISTRIBUTOR_SEED, mint.key().as_ref()],
+        bump = distributor.bump,
+    )]
+    pub distributor: Account<'info, Distributor>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = distributor,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The claimant's ATA, created in the handler if it does not exist yet
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&claimant.key(), &mint.key(), &token_program.key())
+            @ TokenError::InvalidAssociatedTokenAccount,
+    )]
+    pub claimant_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Freeze record PDA of the claimant's ATA; required when a default-frozen mint creates it
+    #[account(mut, seeds = [FREEZE_RECORD_SEED, claimant_token_account.key().as_ref()], bump)]
+    pub claimant_freeze_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the claimant; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), claimant.key().as_ref()], bump)]
+    pub claimant_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the claimant; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), claimant.key().as_ref()], bump)]
+    pub claimant_attestation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ClawbackAirdrop<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [DISTRIBUTOR_SEED, mint.key().as_ref()],
+        bump = distributor.bump,
+        has_one = authority,
+    )]
+    pub distributor: Account<'info, Distributor>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = distributor,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Receives the unclaimed tokens
+    #[account(mut, constraint = destination.mint == mint.key() @ TokenError::MintMismatch)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: KYC attestation PDA of the destination owner; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), destination.owner.as_ref()], bump)]
+    pub destination_attestation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVesting<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Vesting::LEN,
+        seeds = [VESTING_SEED, mint.key().as_ref(), beneficiary.key().as_ref()],
+        bump,
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Funds the vesting escrow
+    #[account(mut)]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The wallet the tokens vest to
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseVesting<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        seeds = [VESTING_SEED, mint.key().as_ref(), beneficiary.key().as_ref()],
+        bump = vesting.bump,
+        has_one = beneficiary,
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The beneficiary's ATA, created in the handler if it does not exist yet
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&beneficiary.key(), &mint.key(), &token_program.key())
+            @ TokenError::InvalidAssociatedTokenAccount,
+    )]
+    pub beneficiary_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Freeze record PDA of the beneficiary's ATA; required when a default-frozen mint creates it
+    #[account(mut, seeds = [FREEZE_RECORD_SEED, beneficiary_token_account.key().as_ref()], bump)]
+    pub beneficiary_freeze_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the beneficiary; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), beneficiary.key().as_ref()], bump)]
+    pub beneficiary_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the beneficiary; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), beneficiary.key().as_ref()], bump)]
+    pub beneficiary_attestation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        seeds = [VESTING_SEED, mint.key().as_ref(), vesting.beneficiary.as_ref()],
+        bump = vesting.bump,
+        has_one = authority,
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Receives the unvested tokens
+    #[account(mut, constraint = destination.mint == mint.key() @ TokenError::MintMismatch)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: KYC attestation PDA of the destination owner; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), destination.owner.as_ref()], bump)]
+    pub destination_attestation: Option<UncheckedAccount<'info>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSale<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Sale::LEN,
+        seeds = [SALE_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub sale: Account<'info, Sale>,
+
+    /// Holds the sale proceeds; funded to rent exemption on creation
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTokens<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        seeds = [SALE_SEED, mint.key().as_ref()],
+        bump = sale.bump,
+    )]
+    pub sale: Account<'info, Sale>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, mint.key().as_ref()],
+        bump = sale.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = SalePurchase::LEN,
+        seeds = [SALE_PURCHASE_SEED, sale.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub purchase: Account<'info, SalePurchase>,
+
+    /// Required once a rate limit is configured for the mint
+    #[account(
+        mut,
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump = rate_limit.bump,
+    )]
+    pub rate_limit: Option<Account<'info, RateLimit>>,
+
+    /// CHECK: The buyer's ATA, created in the handler if it does not exist yet
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&buyer.key(), &mint.key(), &token_program.key())
+            @ TokenError::InvalidAssociatedTokenAccount,
+    )]
+    pub buyer_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Freeze record PDA of the buyer's ATA; required when a default-frozen mint creates it
+    #[account(mut, seeds = [FREEZE_RECORD_SEED, buyer_token_account.key().as_ref()], bump)]
+    pub buyer_freeze_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the buyer; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), buyer.key().as_ref()], bump)]
+    pub buyer_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the buyer; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), buyer.key().as_ref()], bump)]
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SellTokens<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        seeds = [SALE_SEED, mint.key().as_ref()],
+        bump = sale.bump,
+    )]
+    pub sale: Account<'info, Sale>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, mint.key().as_ref()],
+        bump = sale.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut, constraint = !seller_token_account.is_frozen() @ TokenError::AccountFrozen)]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Denylist PDA of the seller; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), seller.key().as_ref()], bump)]
+    pub seller_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSaleProceeds<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [SALE_SEED, mint.key().as_ref()],
+        bump = sale.bump,
+        has_one = authority,
+    )]
+    pub sale: Account<'info, Sale>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, mint.key().as_ref()],
+        bump = sale.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePresale<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Presale::LEN,
+        seeds = [PRESALE_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    /// Holds the presale proceeds; funded to rent exemption on creation
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, presale.key().as_ref()],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurchasePresale<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        seeds = [PRESALE_SEED, mint.key().as_ref()],
+        bump = presale.bump,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, presale.key().as_ref()],
+        bump = presale.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = SalePurchase::LEN,
+        seeds = [SALE_PURCHASE_SEED, presale.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub purchase: Account<'info, SalePurchase>,
+
+    /// Required once a rate limit is configured for the mint
+    #[account(
+        mut,
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump = rate_limit.bump,
+    )]
+    pub rate_limit: Option<Account<'info, RateLimit>>,
+
+    /// CHECK: The buyer's ATA, created in the handler if it does not exist yet
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&buyer.key(), &mint.key(), &token_program.key())
+            @ TokenError::InvalidAssociatedTokenAccount,
+    )]
+    pub buyer_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Freeze record PDA of the buyer's ATA; required when a default-frozen mint creates it
+    #[account(mut, seeds = [FREEZE_RECORD_SEED, buyer_token_account.key().as_ref()], bump)]
+    pub buyer_freeze_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the buyer; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), buyer.key().as_ref()], bump)]
+    pub buyer_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the buyer; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), buyer.key().as_ref()], bump)]
+    pub buyer_attestation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawPresaleProceeds<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [PRESALE_SEED, mint.key().as_ref()],
+        bump = presale.bump,
+        has_one = authority,
+    )]
+    pub presale: Account<'info, Presale>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, presale.key().as_ref()],
+        bump = presale.treasury_bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFaucet<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init_if_needed,
+        payer = mint_authority,
+        space = Faucet::LEN,
+        seeds = [FAUCET_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub faucet: Account<'info, Faucet>,
+
+    #[account(mut)]
+    pub mint_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DripFaucet<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        seeds = [FAUCET_SEED, mint.key().as_ref()],
+        bump = faucet.bump,
+    )]
+    pub faucet: Account<'info, Faucet>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        space = FaucetClaim::LEN,
+        seeds = [FAUCET_CLAIM_SEED, mint.key().as_ref(), wallet.key().as_ref()],
+        bump,
+    )]
+    pub faucet_claim: Account<'info, FaucetClaim>,
+
+    /// Required once a rate limit is configured for the mint
+    #[account(
+        mut,
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump = rate_limit.bump,
+    )]
+    pub rate_limit: Option<Account<'info, RateLimit>>,
+
+    /// CHECK: The wallet's ATA, created in the handler if it does not exist yet
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&wallet.key(), &mint.key(), &token_program.key())
+            @ TokenError::InvalidAssociatedTokenAccount,
+    )]
+    pub wallet_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Freeze record PDA of the wallet's ATA; required when a default-frozen mint creates it
+    #[account(mut, seeds = [FREEZE_RECORD_SEED, wallet_token_account.key().as_ref()], bump)]
+    pub wallet_freeze_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Denylist PDA of the wallet; required once a compliance authority is set
+    #[account(seeds = [DENYLIST_SEED, mint.key().as_ref(), wallet.key().as_ref()], bump)]
+    pub wallet_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the wallet; required while the mint requires an allowlist
+    #[account(seeds = [ATTESTATION_SEED, mint.key().as_ref(), wallet.key().as_ref()], bump)]
+    pub wallet_attestation: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SetComplianceAuthority<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    pub mint_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToDenylist<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = compliance_authority,
+        space = DenylistEntry::LEN,
+        seeds = [DENYLIST_SEED, mint.key().as_ref(), wallet.as_ref()],
+        bump,
+    )]
+    pub denylist_entry: Account<'info, DenylistEntry>,
+
+    /// The wallet's token account; required to auto-freeze
+    #[account(mut)]
+    pub wallet_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Freeze record PDA of `wallet_token_account`; required to auto-freeze
+    #[account(mut)]
+    pub freeze_record: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = mint_state.compliance_authority == Some(compliance_authority.key()) @ TokenError::InvalidComplianceAuthority,
+    )]
+    pub compliance_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromDenylist<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        close = compliance_authority,
+        seeds = [DENYLIST_SEED, mint.key().as_ref(), denylist_entry.wallet.as_ref()],
+        bump = denylist_entry.bump,
+    )]
+    pub denylist_entry: Account<'info, DenylistEntry>,
+
+    #[account(
+        mut,
+        constraint = mint_state.compliance_authority == Some(compliance_authority.key()) @ TokenError::InvalidComplianceAuthority,
+    )]
+    pub compliance_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAllowlistRequired<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        constraint = mint_state.compliance_authority == Some(compliance_authority.key()) @ TokenError::InvalidComplianceAuthority,
+    )]
+    pub compliance_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageAttesters<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init_if_needed,
+        payer = compliance_authority,
+        space = KycConfig::LEN,
+        seeds = [KYC_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub kyc_config: Account<'info, KycConfig>,
+
+    #[account(
+        mut,
+        constraint = mint_state.compliance_authority == Some(compliance_authority.key()) @ TokenError::InvalidComplianceAuthority,
+    )]
+    pub compliance_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct IssueAttestation<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [KYC_CONFIG_SEED, mint.key().as_ref()],
+        bump = kyc_config.bump,
+        constraint = kyc_config.is_attester(&attester.key()) @ TokenError::InvalidAttester,
+    )]
+    pub kyc_config: Account<'info, KycConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = attester,
+        space = Attestation::LEN,
+        seeds = [ATTESTATION_SEED, mint.key().as_ref(), wallet.as_ref()],
+        bump,
+        constraint = attestation.attester == Pubkey::default() || attestation.attester == attester.key()
+            @ TokenError::AttestationHeldByOtherAttester,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        close = attester,
+        seeds = [ATTESTATION_SEED, mint.key().as_ref(), attestation.wallet.as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Issuer of the attestation; receives its rent back
+    #[account(mut, address = attestation.attester)]
+    pub attester: SystemAccount<'info>,
+
+    /// The attester or the mint's compliance authority
+    pub revoker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDefaultAccountState<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// The freeze authority, or the mint authority when the mint state PDA holds it
+    pub authority: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct GetFreezeRecord<'info> {
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [FREEZE_RECORD_SEED, token_account.key().as_ref()],
+        bump = freeze_record.bump,
+    )]
+    pub freeze_record: Account<'info, FreezeRecord>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BatchFreezeResult {
+    /// Accounts whose state changed
+    pub updated: u32,
+    /// Accounts already in the target state, left untouched
+    pub skipped: Vec<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MintCapacity {
+    pub window_seconds: i64,
+    pub mint_limit: u64,
+    pub mint_remaining: u64,
+    pub minter_limit: u64,
+    pub minter_remaining: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MintInfo {
+    pub supply: u64,
+    pub decimals: u8,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct BurnTokens<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(mut, constraint = !token_account.is_frozen() @ TokenError::AccountFrozen)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveDelegate<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Any key may be approved as a delegate
+    pub delegate: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintAuthority<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// The mint's multisig, which can receive an authority without a handover
+    #[account(
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    /// Required when an admin role holder moves a freeze authority the mint state PDA holds
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(mut)]
+    pub current_authority: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: AuthorityKind)]
+pub struct ProposeAuthority<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = current_authority,
+        space = PendingAuthority::LEN,
+        seeds = [PENDING_AUTHORITY_SEED, mint.key().as_ref(), kind.seed()],
+        bump,
+    )]
+    pub pending_authority: Account<'info, PendingAuthority>,
+
+    /// Required when an admin role holder moves a freeze authority the mint state PDA holds
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(mut)]
+    pub current_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_AUTHORITY_SEED, mint.key().as_ref(), pending_authority.kind.seed()],
+        bump = pending_authority.bump,
+        has_one = mint,
+        has_one = proposer,
+        has_one = new_authority,
+    )]
+    pub pending_authority: Account<'info, PendingAuthority>,
+
+    pub new_authority: Signer<'info>,
+
+    /// CHECK: Receives the pending authority rent; checked against the proposal
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuthority<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_AUTHORITY_SEED, mint.key().as_ref(), pending_authority.kind.seed()],
+        bump = pending_authority.bump,
+        has_one = mint,
+        has_one = proposer,
+    )]
+    pub pending_authority: Account<'info, PendingAuthority>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: AuthorityKind)]
+pub struct QueueAuthorityChange<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = current_authority,
+        space = QueuedAuthorityChange::LEN,
+        seeds = [QUEUED_CHANGE_SEED, mint.key().as_ref(), kind.seed()],
+        bump,
+    )]
+    pub queued_change: Account<'info, QueuedAuthorityChange>,
+
+    /// Required when an admin role holder moves a freeze authority the mint state PDA holds
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(mut)]
+    pub current_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAuthorityChange<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        close = current_authority,
+        seeds = [QUEUED_CHANGE_SEED, mint.key().as_ref(), queued_change.kind.seed()],
+        bump = queued_change.bump,
+        has_one = mint,
+        constraint = queued_change.queued_by == current_authority.key() @ TokenError::Unauthorized,
+    )]
+    pub queued_change: Account<'info, QueuedAuthorityChange>,
+
+    /// The mint's multisig, which can receive an authority without accepting
+    #[account(
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    /// Required when an admin role holder moves a freeze authority the mint state PDA holds
+    #[account(
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(mut)]
+    pub current_authority: Signer<'info>,
+
+    /// Must co-sign when the authority moves to a key other than the multisig
+    pub new_authority: Option<Signer<'info>>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuthorityChange<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        close = current_authority,
+        seeds = [QUEUED_CHANGE_SEED, mint.key().as_ref(), queued_change.kind.seed()],
+        bump = queued_change.bump,
+        has_one = mint,
+        constraint = queued_change.queued_by == current_authority.key() @ TokenError::Unauthorized,
+    )]
+    pub queued_change: Account<'info, QueuedAuthorityChange>,
+
+    #[account(mut)]
+    pub current_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthorityDelay<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    pub mint_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRoles<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = mint_authority,
+        space = Roles::LEN,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(mut)]
+    pub mint_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRole<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseMint<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+
+    pub pauser: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMintState<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = mint_authority,
+        space = TokenMintState::LEN,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(mut)]
+    pub mint_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxSupply<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    pub mint_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeAccount<'info> {
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// Required when acting as a holder of the freezer role
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    /// Overwrites a stale record left by a thaw that did not close it
+    #[account(
+        init_if_needed,
+        payer = freeze_authority,
+        space = FreezeRecord::LEN,
+        seeds = [FREEZE_RECORD_SEED, token_account.key().as_ref()],
+        bump,
+    )]
+    pub freeze_record: Account<'info, FreezeRecord>,
+
+    #[account(mut)]
+    pub freeze_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Freezes every `[token_account, freeze_record]` pair, or thaws every `[token_account,
+/// freeze_record, record_payer]` triple, in `remaining_accounts`
+#[derive(Accounts)]
+pub struct BatchFreezeAccounts<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// Required when acting as a holder of the freezer role
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(mut)]
+    pub freeze_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ThawAccount<'info> {
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    /// Required when acting as a holder of the freezer role
+    #[account(
+        mut,
+        seeds = [ROLES_SEED, mint.key().as_ref()],
+        bump = roles.bump,
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    /// CHECK: The account's freeze record PDA, closed if it exists
+    #[account(
+        mut,
+        seeds = [FREEZE_RECORD_SEED, token_account.key().as_ref()],
+        bump,
+    )]
+    pub freeze_record: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the freeze record's rent; must be the payer stored in the record.
+    /// Required when the record exists.
+    #[account(mut)]
+    pub record_payer: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub freeze_authority: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAccount<'info> {
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: This account will receive the rent
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Multisig::LEN,
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = MultisigProposal::LEN,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), &multisig.proposal_count.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut, has_one = multisig)]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_STATE_SEED, mint.key().as_ref()],
+        bump = mint_state.bump,
+    )]
+    pub mint_state: Account<'info, TokenMintState>,
+
+    #[account(
+        seeds = [MULTISIG_SEED, mint.key().as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut, has_one = multisig)]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    /// Required for mints once a rate limit is configured for the mint
+    #[account(
+        mut,
+        seeds = [RATE_LIMIT_SEED, mint.key().as_ref()],
+        bump = rate_limit.bump,
+    )]
+    pub rate_limit: Option<Account<'info, RateLimit>>,
+
+    /// Destination for mints, or the account to freeze/thaw
+    #[account(mut)]
+    pub token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Denylist PDA of the mint destination's owner; verified in the handler
+    pub recipient_denylist_entry: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: KYC attestation PDA of the mint destination's owner; verified in the handler
+    pub recipient_attestation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Freeze record PDA of the account to freeze/thaw; verified in the handler
+    #[account(mut)]
+    pub freeze_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives a thawed account's freeze record rent; checked against the record
+    #[account(mut)]
+    pub record_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Queued authority change PDA for the timelocked authority actions; verified in the handler
+    #[account(mut)]
+    pub queued_change: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Pending authority PDA for the two-step handover actions; verified in the handler
+    #[account(mut)]
+    pub pending_authority: Option<UncheckedAccount<'info>>,
+
+    /// Must co-sign a queued change moving an authority to a key other than the multisig
+    pub new_authority: Option<Signer<'info>>,
+
+    /// Pays for freeze records, queued authority changes and pending handovers, and
+    /// receives the rent of closed queued changes and handovers back
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Option<Program<'info, System>>,
+    #[account(constraint = is_supported_token_program(&token_program.key()) @ TokenError::UnsupportedTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Events for better monitoring and indexing
+#[event]
+pub struct TokenCreated {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub decimals: u8,
+    pub name: String,
+    pub symbol: String,
+}
+
+#[event]
+pub struct MetadataUpdated {
+    pub mint: Pubkey,
+    pub old_name: String,
+    pub new_name: String,
+    pub old_symbol: String,
+    pub new_symbol: String,
+    pub old_uri: String,
+    pub new_uri: String,
+    pub old_seller_fee_basis_points: u16,
+    pub new_seller_fee_basis_points: u16,
+    pub old_creators: Option<Vec<MetadataCreator>>,
+    pub new_creators: Option<Vec<MetadataCreator>>,
+    pub old_update_authority: Pubkey,
+    pub new_update_authority: Pubkey,
+    pub old_is_mutable: bool,
+    pub new_is_mutable: bool,
+}
+
+#[event]
+pub struct TokensMinted {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SupplyCapUpdated {
+    pub mint: Pubkey,
+    pub old_max_supply: u64,
+    pub new_max_supply: u64,
+}
+
+#[event]
+pub struct TokensTransferred {
+    pub mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct TokensBurned {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DelegateApproved {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct AccountFrozen {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub reason: FreezeReason,
+    pub case_id: String,
+}
+
+#[event]
+pub struct AccountThawed {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+}
+
+#[event]
+pub struct MultisigCreated {
+    pub mint: Pubkey,
+    pub multisig: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub signer: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub executor: Pubkey,
+    pub action: MultisigAction,
+}
+
+#[event]
+pub struct AuthorityChangeProposed {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub current_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityChangeAccepted {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityChangeCancelled {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub proposer: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityDelayUpdated {
+    pub mint: Pubkey,
+    pub old_delay: i64,
+    pub new_delay: i64,
+}
+
+#[event]
+pub struct AuthorityChangeQueued {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub current_authority: Pubkey,
+    pub new_authority: Option<Pubkey>,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct QueuedAuthorityChangeExecuted {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub old_authority: Pubkey,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct QueuedAuthorityChangeCancelled {
+    pub mint: Pubkey,
+    pub kind: AuthorityKind,
+    pub current_authority: Pubkey,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+    pub role: Role,
+    pub allowance: Option<u64>,
+    pub granted_by: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+    pub role: Role,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct MintPaused {
+    pub mint: Pubkey,
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct MintUnpaused {
+    pub mint: Pubkey,
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct RateLimitConfigured {
+    pub mint: Pubkey,
+    pub window_seconds: i64,
+    pub mint_limit: u64,
+    pub minter_limit: u64,
+}
+
+#[event]
+pub struct DistributorCreated {
+    pub mint: Pubkey,
+    pub distributor: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_allocation: u64,
+    pub num_recipients: u32,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AirdropClaimed {
+    pub mint: Pubkey,
+    pub claimant: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AirdropClawedBack {
+    pub mint: Pubkey,
+    pub distributor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VestingCreated {
+    pub mint: Pubkey,
+    pub vesting: Pubkey,
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+    pub revocable: bool,
+}
+
+#[event]
+pub struct VestedTokensReleased {
+    pub mint: Pubkey,
+    pub vesting: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub total_released: u64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub mint: Pubkey,
+    pub vesting: Pubkey,
+    pub beneficiary: Pubkey,
+    pub unvested_amount: u64,
+}
+
+#[event]
+pub struct SaleCreated {
+    pub mint: Pubkey,
+    pub sale: Pubkey,
+    pub curve: PriceCurve,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub wallet_cap: u64,
+    pub sell_back: bool,
+}
+
+#[event]
+pub struct TokensPurchased {
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub cost: u64,
+}
+
+#[event]
+pub struct TokensSoldBack {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub proceeds: u64,
+}
+
+#[event]
+pub struct SaleProceedsWithdrawn {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PresaleCreated {
+    pub mint: Pubkey,
+    pub presale: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub price: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct PresalePurchased {
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub cost: u64,
+    pub total_purchased: u64,
+}
+
+#[event]
+pub struct PresaleProceedsWithdrawn {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FaucetConfigured {
+    pub mint: Pubkey,
+    pub drip_amount: u64,
+    pub cooldown_seconds: i64,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct FaucetDripped {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub next_drip_at: i64,
+}
+
+#[event]
+pub struct ComplianceAuthorityUpdated {
+    pub mint: Pubkey,
+    pub old_authority: Option<Pubkey>,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct WalletDenylisted {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub case_id: String,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct WalletRemovedFromDenylist {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct DefaultAccountStateUpdated {
+    pub mint: Pubkey,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct AllowlistRequirementUpdated {
+    pub mint: Pubkey,
+    pub required: bool,
+}
+
+#[event]
+pub struct AttesterAdded {
+    pub mint: Pubkey,
+    pub attester: Pubkey,
+}
+
+#[event]
+pub struct AttesterRemoved {
+    pub mint: Pubkey,
+    pub attester: Pubkey,
+}
+
+#[event]
+pub struct AttestationIssued {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub attester: Pubkey,
+    pub jurisdiction: [u8; 2],
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AttestationRevoked {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub revoked_by: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MintDestination {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[program]
+pub mod spl_token_mint {
+    use super::*;
+
+    /// Initialize a new mint with specified parameters (basic version without metadata)
+    pub fn initialize_mint(
+        ctx: Context<InitializeMint>,
+        decimals: u8,
+        mint_authority: Pubkey,
+        freeze_authority: Option<Pubkey>,
+        max_supply: Option<u64>,
+        program_mint_authority: bool,
+        default_frozen: bool,
+    ) -> Result<()> {
+        // Validate decimals
+        require!(decimals <= 9, TokenError::InvalidDecimals);
+
+        // Default-frozen mints freeze the accounts they create with the mint state PDA
+        require!(
+            !default_frozen || freeze_authority == Some(ctx.accounts.mint_state.key()),
+            TokenError::FreezeAuthorityRequired
+        );
+
+        // In program authority mode the mint state PDA holds the mint authority and
+        // `mint_authority` may only mint through this program
+        let token_mint_authority = if program_mint_authority {
+            ctx.accounts.mint_state.key()
+        } else {
+            mint_authority
+        };
+
+        // Token-2022 mints get the DefaultAccountState extension, which must be
+        // initialized before the mint itself
+        if default_frozen && ctx.accounts.token_program.key() == anchor_spl::token_2022::ID {
+            let cpi_accounts = DefaultAccountStateInitialize {
+                token_program_id: ctx.accounts.token_program.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            default_account_state_initialize(cpi_ctx, &AccountState::Frozen)?;
+        }
+
+        // Initialize the mint using CPI to the token program
+        let cpi_accounts = token_interface::InitializeMint {
+            mint: ctx.accounts.mint.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        token_interface::initialize_mint(cpi_ctx, decimals, &token_mint_authority, freeze_authority.as_ref())?;
+
+        // Record the mint state and supply cap (None means uncapped)
+        ctx.accounts.mint_state.set_inner(TokenMintState {
+            mint: ctx.accounts.mint.key(),
+            mint_authority,
+            total_minted: 0,
+            max_supply: max_supply.unwrap_or(u64::MAX),
+            authority_delay: 0,
+            paused: false,
+            rate_limited: false,
+            compliance_authority: None,
+            kyc_required: false,
+            default_frozen,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.mint_state,
+        });
+
+        msg!("Token mint initialized successfully!");
+        msg!("Mint address: {}", ctx.accounts.mint.key());
+        msg!("Mint authority: {}", token_mint_authority);
+        if program_mint_authority {
+            msg!("Minting operator: {}", mint_authority);
+        }
+        if let Some(freeze_auth) = freeze_authority {
+            msg!("Freeze authority: {}", freeze_auth);
+        } else {
+            msg!("Freeze authority: None");
+        }
+        msg!("Decimals: {}", decimals);
+        msg!("Accounts start frozen: {}", default_frozen);
+    
+        Ok(())
+    }
+
+    /// Create a new token with metadata (name, symbol, logo URI)
+    /// The payer signs as mint authority for the Metaplex CPI and then hands it to `mint_authority`
+    ///
+    /// New token accounts start thawed. Default-frozen accounts are an `initialize_mint`
+    /// option; a legacy mint created here can turn them on later with
+    /// `set_default_account_state` once the mint state PDA holds its freeze authority.
+    pub fn create_token_with_metadata(
+        ctx: Context<CreateTokenWithMetadata>,
+        decimals: u8,
+        metadata: TokenMetadata,
+        mint_authority: Pubkey,
+        freeze_authority: Option<Pubkey>,
+        max_supply: Option<u64>,
+    ) -> Result<()> {
+        // Validate inputs
+        require!(decimals <= 9, TokenError::InvalidDecimals);
+        metadata.validate()?;
+
+        // First, initialize the mint with the payer as mint authority
+        let cpi_accounts = token_interface::InitializeMint {
+            mint: ctx.accounts.mint.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        token_interface::initialize_mint(cpi_ctx, decimals, &ctx.accounts.payer.key(), freeze_authority.as_ref())?;
+
+        // Create metadata via Metaplex CreateMetadataAccountV3
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let metadata_account = ctx.accounts.metadata.to_account_info();
+        let mint = ctx.accounts.mint.to_account_info();
+        let payer = ctx.accounts.payer.to_account_info();
+        let update_authority = ctx.accounts.update_authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let rent = ctx.accounts.rent.to_account_info();
+
+        CreateMetadataAccountV3CpiBuilder::new(&token_metadata_program)
+            .metadata(&metadata_account)
+            .mint(&mint)
+            .mint_authority(&payer)
+            .payer(&payer)
+            .update_authority(&update_authority, update_authority.is_signer)
+            .system_program(&system_program)
+            .rent(Some(&rent))
+            .data(metadata.to_data_v2())
+            .is_mutable(metadata.is_mutable)
+            .invoke()?;
+
+        // Hand the mint authority over if it differs from the payer
+        if mint_authority != ctx.accounts.payer.key() {
+            let cpi_accounts = SplSetAuthority {
+                account_or_mint: mint,
+                current_authority: payer,
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            token_interface::set_authority(cpi_ctx, AuthorityType::MintTokens, Some(mint_authority))?;
+        }
+
+        // Record the mint state and supply cap (None means uncapped)
+        ctx.accounts.mint_state.set_inner(TokenMintState {
+            mint: ctx.accounts.mint.key(),
+            mint_authority,
+            total_minted: 0,
+            max_supply: max_supply.unwrap_or(u64::MAX),
+            authority_delay: 0,
+            paused: false,
+            rate_limited: false,
+            compliance_authority: None,
+            kyc_required: false,
+            default_frozen: false,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.mint_state,
+        });
+
+        // Emit event
+        emit!(TokenCreated {
+            mint: ctx.accounts.mint.key(),
+            authority: mint_authority,
+            decimals,
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+        });
+
+        msg!("Token created successfully!");
+        msg!("Mint address: {}", ctx.accounts.mint.key());
+        msg!("Metadata address: {}", ctx.accounts.metadata.key());
+        msg!("Name: {}", metadata.name);
+        msg!("Symbol: {}", metadata.symbol);
+        msg!("URI: {}", metadata.uri);
+        msg!("Decimals: {}", decimals);
+
+        Ok(())
+    }
+
+    /// Create the mint state PDA for a mint made before it existed, such as one from
+    /// the original `initialize_mint`. Signed by the mint's current mint authority.
+    pub fn initialize_mint_state(ctx: Context<InitializeMintState>, max_supply: Option<u64>) -> Result<()> {
+        // Verify mint authority
+        let mint = &ctx.accounts.mint;
+        require!(
+            Option::<Pubkey>::from(mint.mint_authority) == Some(ctx.accounts.mint_authority.key()),
+            TokenError::Unauthorized
+        );
+
+        // The cap cannot start below what is already in circulation
+        let max_supply = max_supply.unwrap_or(u64::MAX);
+        require!(max_supply >= mint.supply, TokenError::InvalidSupplyCap);
+
+        ctx.accounts.mint_state.set_inner(TokenMintState {
+            mint: mint.key(),
+            mint_authority: ctx.accounts.mint_authority.key(),
+            total_minted: mint.supply,
+            max_supply,
+            authority_delay: 0,
+            paused: false,
+            rate_limited: false,
+            compliance_authority: None,
+            kyc_required: false,
+            default_frozen: false,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.mint_state,
+        });
+
+        msg!("Mint state initialized for existing mint: {}", mint.key());
+        msg!("Supply cap: {}", max_supply);
+
+        Ok(())
+    }
+
+    /// Update the Metaplex metadata of a token (signed by the current update authority)
+    /// Setting `metadata.is_mutable` to false locks the metadata permanently
+    pub fn update_token_metadata(
+        ctx: Context<UpdateTokenMetadata>,
+        metadata: TokenMetadata,
+        new_update_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        // Validate inputs
+        metadata.validate()?;
+
+        let current = Metadata::try_from(&ctx.accounts.metadata.to_account_info())
+            .map_err(|_| TokenError::InvalidMetadataAccount)?;
+
+        // Verify update authority and mutability
+        validate_metadata_update(&current.update_authority, current.is_mutable, &ctx.accounts.update_authority.key())?;
+
+        // Update metadata via Metaplex UpdateMetadataAccountV2
+        let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+        let metadata_account = ctx.accounts.metadata.to_account_info();
+        let update_authority = ctx.accounts.update_authority.to_account_info();
+
+        let mut update = UpdateMetadataAccountV2CpiBuilder::new(&token_metadata_program);
+        update
+            .metadata(&metadata_account)
+            .update_authority(&update_authority)
+            .data(metadata.to_data_v2())
+            .is_mutable(metadata.is_mutable);
+        if let Some(new_authority) = new_update_authority {
+            update.new_update_authority(new_authority);
+        }
+        update.invoke()?;
+
+        // Emit event (Metaplex pads stored strings with null bytes)
+        emit!(MetadataUpdated {
+            mint: ctx.accounts.mint.key(),
+            old_name: current.name.trim_end_matches('\0').to_string(),
+            new_name: metadata.name.clone(),
+            old_symbol: current.symbol.trim_end_matches('\0').to_string(),
+            new_symbol: metadata.symbol.clone(),
+            old_uri: current.uri.trim_end_matches('\0').to_string(),
+            new_uri: metadata.uri.clone(),
+            old_seller_fee_basis_points: current.seller_fee_basis_points,
+            new_seller_fee_basis_points: metadata.seller_fee_basis_points,
+            old_creators: current
+                .creators
+                .map(|creators| creators.into_iter().map(MetadataCreator::from).collect()),
+            new_creators: metadata.creators.clone(),
+            old_update_authority: current.update_authority,
+            new_update_authority: new_update_authority.unwrap_or(current.update_authority),
+            old_is_mutable: current.is_mutable,
+            new_is_mutable: metadata.is_mutable,
+        });
+
+        msg!("Metadata updated for mint: {}", ctx.accounts.mint.key());
+        if let Some(new_authority) = new_update_authority {
+            msg!("Update authority changed to: {}", new_authority);
+        }
+        if !metadata.is_mutable {
+            msg!("Metadata is now immutable");
+        }
+
+        Ok(())
+    }
+
+    /// Mint tokens to a destination account (SPL Token mints only; Token-2022
+    /// mints use `mint_tokens_checked`)
+    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+        require_legacy_token_program(&ctx.accounts.token_program.key())?;
+        let decimals = ctx.accounts.mint.decimals;
+        mint_tokens_checked(ctx, amount, decimals)
+    }
+
+    /// Mint tokens to a destination account, verifying the caller's `decimals`
+    pub fn mint_tokens_checked(ctx: Context<MintTokens>, amount: u64, decimals: u8) -> Result<()> {
+        // Validate amount is not zero and decimals match
+        require!(amount > 0, TokenError::InvalidAmount);
+        require!(decimals == ctx.accounts.mint.decimals, TokenError::DecimalsMismatch);
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        // Verify mint authority, or a minter role with enough allowance
+        let program_authority = authorize_minter(
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_state,
+            ctx.accounts.roles.as_mut(),
+            &ctx.accounts.mint_authority.key(),
+            amount,
+        )?;
+
+        // Check the denylist and KYC allowlist
+        require_not_denylisted(
+            &ctx.accounts.mint_state,
+            ctx.accounts.destination_denylist_entry.as_ref(),
+        )?;
+        require_attested(
+            &ctx.accounts.mint_state,
+            ctx.accounts.destination_attestation.as_ref(),
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Check for potential overflow, the supply cap and the rate limit
+        ctx.accounts.mint_state.check_supply_cap(ctx.accounts.mint.supply, amount)?;
+        enforce_rate_limit(
+            &ctx.accounts.mint_state,
+            ctx.accounts.rate_limit.as_mut(),
+            &ctx.accounts.mint_authority.key(),
+            amount,
+        )?;
+
+        // Create the destination ATA if it does not exist yet
+        let new_account = create_associated_account(
+            &ctx.accounts.destination,
+            &ctx.accounts.destination_owner,
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_authority,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+        )?;
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            thaw_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.destination,
+                &ctx.accounts.token_program,
+            )?;
+        }
+        let destination = TokenAccount::try_deserialize(&mut &ctx.accounts.destination.try_borrow_data()?[..])?;
+        require!(!destination.is_frozen(), TokenError::AccountFrozen);
+
+        // Mint tokens, signing with the mint state PDA when it holds the authority
+        let authority = if program_authority {
+            ctx.accounts.mint_state.to_account_info()
+        } else {
+            ctx.accounts.mint_authority.to_account_info()
+        };
+        let cpi_accounts = SplMintToChecked {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority,
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::mint_to_checked(cpi_ctx, amount, decimals)?;
+        ctx.accounts.mint_state.record_mint(amount)?;
+
+        // Emit event
+        emit!(TokensMinted {
+            mint: ctx.accounts.mint.key(),
+            recipient: ctx.accounts.destination.key(),
+            amount,
+        });
+
+        // New accounts of a default-frozen mint stay frozen once delivered to
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            freeze_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.destination.to_account_info(),
+                ctx.accounts.destination_freeze_record.as_deref(),
+                &ctx.accounts.mint_authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+            )?;
+        }
+
+        msg!("Minted {} tokens to {}", amount, ctx.accounts.destination.key());
+    
+        Ok(())
+    }
+
+    /// Batch mint tokens to multiple destinations (limited to 10 for safety)
+    ///
+    /// `remaining_accounts` must hold one `[destination_ata, recipient]` pair per
+    /// destination, in the same order as `destinations`, followed by the recipient's
+    /// denylist PDA once a compliance authority is set, its KYC attestation PDA
+    /// while an allowlist is required and the ATA's freeze record PDA for
+    /// default-frozen mints. Missing ATAs are created.
+    pub fn batch_mint_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchMintTokens<'info>>,
+        destinations: Vec<MintDestination>,
+    ) -> Result<()> {
+        // Validate batch size and amounts
+        validate_batch_destinations(&destinations)?;
+        ctx.accounts.mint_state.require_not_paused()?;
+        let default_freeze = needs_default_freeze(&ctx.accounts.mint_state);
+        let per_destination = batch_accounts_per_destination(&ctx.accounts.mint_state, default_freeze);
+        require!(
+            ctx.remaining_accounts.len() == destinations.len() * per_destination,
+            TokenError::InvalidRemainingAccounts
+        );
+
+        // Verify mint authority
+        let program_authority = validate_minter(
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_state,
+            &ctx.accounts.mint_authority.key(),
+        )?;
+
+        // Calculate total amount and check for overflow and the supply cap
+        let total_amount = calculate_batch_total(&destinations)?;
+        ctx.accounts.mint_state.check_supply_cap(ctx.accounts.mint.supply, total_amount)?;
+        enforce_rate_limit(
+            &ctx.accounts.mint_state,
+            ctx.accounts.rate_limit.as_mut(),
+            &ctx.accounts.mint_authority.key(),
+            total_amount,
+        )?;
+
+        // Check every ATA before minting anything so one bad entry fails the whole batch
+        let mint_key = ctx.accounts.mint.key();
+        let now = Clock::get()?.unix_timestamp;
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let pairs = ctx.remaining_accounts.chunks(per_destination);
+        for (dest, pair) in destinations.iter().zip(pairs.clone()) {
+            require!(
+                pair[1].key() == dest.recipient,
+                TokenError::InvalidAssociatedTokenAccount
+            );
+            check_batch_recipient(
+                &ctx.accounts.mint_state,
+                &mint_key,
+                &dest.recipient,
+                &pair[BATCH_MINT_ACCOUNTS_PER_DESTINATION..],
+                now,
+            )?;
+            require!(
+                pair[0].key() == get_associated_token_address_with_program_id(
+                    &dest.recipient,
+                    &mint_key,
+                    &ctx.accounts.token_program.key(),
+                ),
+                TokenError::InvalidAssociatedTokenAccount
+            );
+        }
+
+        for (dest, pair) in destinations.iter().zip(pairs) {
+            let destination = &pair[0];
+            let recipient = &pair[1];
+
+            // Create the destination ATA if it does not exist yet
+            let new_account = create_associated_account(
+                destination,
+                recipient,
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_authority,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+                &ctx.accounts.associated_token_program,
+            )?;
+            if new_account && default_freeze {
+                thaw_new_account(&ctx.accounts.mint, &ctx.accounts.mint_state, destination, &ctx.accounts.token_program)?;
+            }
+
+            // Mint tokens, signing with the mint state PDA when it holds the authority
+            let authority = if program_authority {
+                ctx.accounts.mint_state.to_account_info()
+            } else {
+                ctx.accounts.mint_authority.to_account_info()
+            };
+            let cpi_accounts = SplMintToChecked {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: destination.clone(),
+                authority,
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            token_interface::mint_to_checked(cpi_ctx, dest.amount, ctx.accounts.mint.decimals)?;
+
+            // Emit event
+            emit!(TokensMinted {
+                mint: mint_key,
+                recipient: destination.key(),
+                amount: dest.amount,
+            });
+
+            // New accounts of a default-frozen mint stay frozen once delivered to
+            if new_account && default_freeze {
+                freeze_new_account(
+                    &ctx.accounts.mint,
+                    &ctx.accounts.mint_state,
+                    destination,
+                    pair.last(),
+                    &ctx.accounts.mint_authority.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                )?;
+            }
+        }
+
+        ctx.accounts.mint_state.record_mint(total_amount)?;
+
+        msg!("Batch minted {} tokens to {} destinations",
+             total_amount, destinations.len());
+
+        Ok(())
+    }
+
+    /// Transfer tokens between accounts (SPL Token mints only; Token-2022 mints
+    /// use `transfer_tokens_checked`)
+    pub fn transfer_tokens(ctx: Context<TransferTokens>, amount: u64) -> Result<()> {
+        require_legacy_token_program(&ctx.accounts.token_program.key())?;
+        let decimals = ctx.accounts.mint.decimals;
+        transfer_tokens_checked(ctx, amount, decimals)
+    }
+
+    /// Transfer tokens between accounts, verifying the caller's `decimals`
+    pub fn transfer_tokens_checked(ctx: Context<TransferTokens>, amount: u64, decimals: u8) -> Result<()> {
+        // Validate amount and decimals
+        require!(amount > 0, TokenError::InvalidAmount);
+        require!(decimals == ctx.accounts.mint.decimals, TokenError::DecimalsMismatch);
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        // Check sufficient balance
+        require!(
+            ctx.accounts.from.amount >= amount,
+            TokenError::InsufficientFunds
+        );
+
+        // Verify authority owns the source account or is its delegate
+        authorize_spender(&ctx.accounts.from, &ctx.accounts.authority.key(), amount)?;
+
+        // Verify both accounts have the same mint
+        require!(
+            ctx.accounts.from.mint == ctx.accounts.to.mint,
+            TokenError::MintMismatch
+        );
+        require!(
+            ctx.accounts.from.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
+        );
+
+        // Additional security: prevent self-transfer
+        require!(
+            ctx.accounts.from.key() != ctx.accounts.to.key(),
+            TokenError::InvalidTransfer
+        );
+
+        // Check the denylist and KYC allowlist
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.sender_denylist_entry.as_ref())?;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.authority_denylist_entry.as_ref())?;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.recipient_denylist_entry.as_ref())?;
+        require_attested(
+            &ctx.accounts.mint_state,
+            ctx.accounts.recipient_attestation.as_ref(),
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Perform transfer
+        let cpi_accounts = SplTransferChecked {
+            from: ctx.accounts.from.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+
+        // Emit event
+        emit!(TokensTransferred {
+            mint: ctx.accounts.from.mint,
+            from: ctx.accounts.from.key(),
+            to: ctx.accounts.to.key(),
+            amount,
+            decimals,
+        });
+
+        msg!(
+            "Transferred {} tokens from {} to {}",
+            amount,
+            ctx.accounts.from.key(),
+            ctx.accounts.to.key()
+        );
+
+        Ok(())
+    }
+
+    /// Get mint information
+    pub fn get_mint_info(ctx: Context<GetMintInfo>) -> Result<MintInfo> {
+        let mint = &ctx.accounts.mint;
+    
+        Ok(MintInfo {
+            supply: mint.supply,
+            decimals: mint.decimals,
+            mint_authority: mint.mint_authority.into(),
+            freeze_authority: mint.freeze_authority.into(),
+        })
+    }
+
+    /// Configure the mint's rate limit: at most `mint_limit` base units per rolling
+    /// window overall and `minter_limit` per minting key. Once configured the limit
+    /// can only be tightened, and capacity already used stays used.
+    pub fn configure_rate_limit(
+        ctx: Context<ConfigureRateLimit>,
+        window_seconds: i64,
+        mint_limit: u64,
+        minter_limit: u64,
+    ) -> Result<()> {
+        // Verify mint authority
+        validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &ctx.accounts.mint_authority.key())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        if ctx.accounts.mint_state.rate_limited {
+            ctx.accounts
+                .rate_limit
+                .tighten(window_seconds, mint_limit, minter_limit, now)?;
+        } else {
+            RateLimit::validate_config(window_seconds, mint_limit, minter_limit)?;
+            ctx.accounts.rate_limit.set_inner(RateLimit {
+                mint: ctx.accounts.mint.key(),
+                window_seconds,
+                mint_limit,
+                minter_limit,
+                mint_bucket: RateBucket::full(mint_limit, window_seconds, now),
+                minters: Vec::new(),
+                bump: ctx.bumps.rate_limit,
+            });
+            ctx.accounts.mint_state.rate_limited = true;
+        }
+
+        // Emit event
+        emit!(RateLimitConfigured {
+            mint: ctx.accounts.mint.key(),
+            window_seconds,
+            mint_limit,
+            minter_limit,
+        });
+
+        msg!("Rate limit set: {} per mint, {} per minter every {}s", mint_limit, minter_limit, window_seconds);
+
+        Ok(())
+    }
+
+    /// Get the minting capacity left in the current window for the mint and `minter`
+    pub fn get_mint_capacity(ctx: Context<GetMintCapacity>, minter: Pubkey) -> Result<MintCapacity> {
+        let rate_limit = &ctx.accounts.rate_limit;
+        let (mint_remaining, minter_remaining) =
+            rate_limit.remaining(&minter, Clock::get()?.unix_timestamp);
+
+        Ok(MintCapacity {
+            window_seconds: rate_limit.window_seconds,
+            mint_limit: rate_limit.mint_limit,
+            mint_remaining,
+            minter_limit: rate_limit.minter_limit,
+            minter_remaining,
+        })
+    }
+
+    /// Transfer from one account to multiple recipients (limited to 10 for safety)
+    ///
+    /// `remaining_accounts` must hold one `[destination, recipient]` pair per
+    /// destination, in the same order as `destinations`, followed by the recipient's
+    /// denylist PDA once a compliance authority is set and its KYC attestation PDA
+    /// while an allowlist is required. With `create_missing_atas`
+    /// each destination must be the recipient's ATA, created if it does not exist,
+    /// and default-frozen mints also take the ATA's freeze record PDA.
+    pub fn batch_transfer_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchTransferTokens<'info>>,
+        destinations: Vec<MintDestination>,
+        create_missing_atas: bool,
+    ) -> Result<()> {
+        // Validate batch size and amounts
+        validate_batch_destinations(&destinations)?;
+        ctx.accounts.mint_state.require_not_paused()?;
+        let default_freeze =
+            create_missing_atas && needs_default_freeze(&ctx.accounts.mint_state);
+        let per_destination = batch_accounts_per_destination(&ctx.accounts.mint_state, default_freeze);
+        require!(
+            ctx.remaining_accounts.len() == destinations.len() * per_destination,
+            TokenError::InvalidRemainingAccounts
+        );
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.sender_denylist_entry.as_ref())?;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.authority_denylist_entry.as_ref())?;
+
+        // Check the total against the balance and the authority's rights
+        let total_amount = calculate_batch_total(&destinations)?;
+        require!(
+            ctx.accounts.from.amount >= total_amount,
+            TokenError::InsufficientFunds
+        );
+        authorize_spender(&ctx.accounts.from, &ctx.accounts.authority.key(), total_amount)?;
+        require!(
+            ctx.accounts.from.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
+        );
+
+        // Check every leg's accounts before moving anything
+        let mint_key = ctx.accounts.mint.key();
+        let now = Clock::get()?.unix_timestamp;
+        let from_key = ctx.accounts.from.key();
+        let pairs = ctx.remaining_accounts.chunks(per_destination);
+        for (dest, pair) in destinations.iter().zip(pairs.clone()) {
+            require!(
+                pair[1].key() == dest.recipient,
+                TokenError::InvalidRemainingAccounts
+            );
+            check_batch_recipient(
+                &ctx.accounts.mint_state,
+                &mint_key,
+                &dest.recipient,
+                &pair[BATCH_MINT_ACCOUNTS_PER_DESTINATION..],
+                now,
+            )?;
+            require!(pair[0].key() != from_key, TokenError::InvalidTransfer);
+            if create_missing_atas {
+                require!(
+                    pair[0].key() == get_associated_token_address_with_program_id(
+                        &dest.recipient,
+                        &mint_key,
+                        &ctx.accounts.token_program.key(),
+                    ),
+                    TokenError::InvalidAssociatedTokenAccount
+                );
+            }
+        }
+
+        let decimals = ctx.accounts.mint.decimals;
+        for (dest, pair) in destinations.iter().zip(pairs) {
+            let destination = &pair[0];
+            let recipient = &pair[1];
+
+            // Create the destination ATA if requested and it does not exist yet
+            let new_account = create_missing_atas
+                && create_associated_account(
+                    destination,
+                    recipient,
+                    &ctx.accounts.mint,
+                    &ctx.accounts.authority,
+                    &ctx.accounts.system_program,
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.associated_token_program,
+                )?;
+            if new_account && default_freeze {
+                thaw_new_account(&ctx.accounts.mint, &ctx.accounts.mint_state, destination, &ctx.accounts.token_program)?;
+            }
+
+            // Same checks as `transfer_tokens`, per leg
+            let to = InterfaceAccount::<TokenAccount>::try_from(destination)?;
+            require!(to.mint == mint_key, TokenError::MintMismatch);
+            require!(to.owner == dest.recipient, TokenError::InvalidOwner);
+            require!(!to.is_frozen(), TokenError::AccountFrozen);
+
+            let cpi_accounts = SplTransferChecked {
+                from: ctx.accounts.from.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: destination.clone(),
+                authority: ctx.accounts.authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            token_interface::transfer_checked(cpi_ctx, dest.amount, decimals)?;
+
+            // Emit event
+            emit!(TokensTransferred {
+                mint: mint_key,
+                from: from_key,
+                to: destination.key(),
+                amount: dest.amount,
+                decimals,
+            });
+
+            // New accounts of a default-frozen mint stay frozen once delivered to
+            if new_account && default_freeze {
+                freeze_new_account(
+                    &ctx.accounts.mint,
+                    &ctx.accounts.mint_state,
+                    destination,
+                    pair.last(),
+                    &ctx.accounts.authority,
+                    &ctx.accounts.system_program,
+                    &ctx.accounts.token_program,
+                )?;
+            }
+        }
+
+        msg!("Batch transferred {} tokens to {} destinations",
+             total_amount, destinations.len());
+
+        Ok(())
+    }
+
+    /// Burn tokens from an account (SPL Token mints only; Token-2022 mints use
+    /// `burn_tokens_checked`)
+    pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
+        require_legacy_token_program(&ctx.accounts.token_program.key())?;
+        let decimals = ctx.accounts.mint.decimals;
+        burn_tokens_checked(ctx, amount, decimals)
+    }
+
+    /// Burn tokens from an account, verifying the caller's `decimals`
+    pub fn burn_tokens_checked(ctx: Context<BurnTokens>, amount: u64, decimals: u8) -> Result<()> {
+        // Validate amount and decimals
+        require!(amount > 0, TokenError::InvalidAmount);
+        require!(decimals == ctx.accounts.mint.decimals, TokenError::DecimalsMismatch);
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        // Check sufficient balance
+        require!(
+            ctx.accounts.token_account.amount >= amount,
+            TokenError::BurnAmountExceedsBalance
+        );
+
+        // Verify authority owns the token account or is its delegate
+        authorize_spender(&ctx.accounts.token_account, &ctx.accounts.authority.key(), amount)?;
+
+        // Verify the token account belongs to the correct mint
+        require!(
+            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
+        );
+
+        // Burn tokens
+        let cpi_accounts = SplBurnChecked {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.token_account.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        token_interface::burn_checked(cpi_ctx, amount, decimals)?;
+
+        // Emit event
+        emit!(TokensBurned {
+            mint: ctx.accounts.mint.key(),
+            account: ctx.accounts.token_account.key(),
+            amount,
+        });
+
+        msg!("Burned {} tokens from {}", amount, ctx.accounts.token_account.key());
+
+        Ok(())
+    }
+
+    /// Let `delegate` transfer or burn up to `amount` tokens from the owner's account
+    pub fn approve_delegate(ctx: Context<ApproveDelegate>, amount: u64) -> Result<()> {
+        // Validate amount
+        require!(amount > 0, TokenError::InvalidAmount);
+
+        // Verify owner and mint
+        require!(
+            ctx.accounts.token_account.owner == ctx.accounts.owner.key(),
+            TokenError::InvalidOwner
+        );
+        require!(
+            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
+        );
+
+        // Approve delegate
+        let cpi_accounts = SplApprove {
+            to: ctx.accounts.token_account.to_account_info(),
+            delegate: ctx.accounts.delegate.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        token_interface::approve(cpi_ctx, amount)?;
+
+        // Emit event
+        emit!(DelegateApproved {
+            mint: ctx.accounts.mint.key(),
+            account: ctx.accounts.token_account.key(),
+            delegate: ctx.accounts.delegate.key(),
+            amount,
+        });
+
+        msg!("Approved {} to spend {} tokens", ctx.accounts.delegate.key(), amount);
+
+        Ok(())
+    }
+
+    /// Remove the delegate from the owner's account
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        // Verify owner and mint
+        require!(
+            ctx.accounts.token_account.owner == ctx.accounts.owner.key(),
+            TokenError::InvalidOwner
+        );
+        require!(
+            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
+        );
+        let delegate = Option::<Pubkey>::from(ctx.accounts.token_account.delegate).ok_or(TokenError::NoDelegate)?;
+
+        // Revoke delegate
+        let cpi_accounts = SplRevoke {
+            source: ctx.accounts.token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        token_interface::revoke(cpi_ctx)?;
+
+        // Emit event
+        emit!(DelegateRevoked {
+            mint: ctx.accounts.mint.key(),
+            account: ctx.accounts.token_account.key(),
+            delegate,
+        });
+
+        msg!("Revoked delegate {}", delegate);
+
+        Ok(())
+    }
+
+    /// Revoke the mint authority, or hand it to the mint's multisig.
+    /// Any other change goes through `propose_authority` / `accept_authority`.
+    pub fn set_mint_authority(
+        ctx: Context<SetMintAuthority>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        // Verify current authority
+        let program_authority = validate_minter(
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_state,
+            &ctx.accounts.current_authority.key(),
+        )?;
+
+        let multisig = ctx.accounts.multisig.as_ref().map(|multisig| multisig.key());
+        require!(
+            is_immediate_authority_change(new_authority, ctx.accounts.mint_state.key(), multisig),
+            TokenError::AuthorityChangeRequiresAcceptance
+        );
+        ctx.accounts.mint_state.require_no_timelock()?;
+
+        // In program authority mode the PDA keeps the mint authority and only the
+        // minting operator rotates, so no key can bypass the program's checks
+        if let (true, Some(operator)) = (program_authority, new_authority) {
+            ctx.accounts.mint_state.mint_authority = operator;
+            msg!("Minting operator changed to: {}", operator);
+            return Ok(());
+        }
+
+        // Set new mint authority (can be None to revoke mint authority permanently)
+        let current_authority = if program_authority {
+            ctx.accounts.mint_state.to_account_info()
+        } else {
+            ctx.accounts.current_authority.to_account_info()
+        };
+        let cpi_accounts = SplSetAuthority {
+            account_or_mint: ctx.accounts.mint.to_account_info(),
+            current_authority,
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::set_authority(cpi_ctx, AuthorityType::MintTokens, new_authority)?;
+        ctx.accounts.mint_state.mint_authority = new_authority.unwrap_or_default();
+
+        match new_authority {
+            Some(authority) => {
+                msg!("Mint authority changed to: {}", authority);
+            }
+            None => {
+                msg!("Mint authority revoked permanently");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lower the supply cap of a mint (the cap can never be raised)
+    pub fn set_max_supply(ctx: Context<SetMaxSupply>, new_max_supply: u64) -> Result<()> {
+        // Verify mint authority
+        validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &ctx.accounts.mint_authority.key())?;
+
+        let mint_state = &mut ctx.accounts.mint_state;
+        let old_max_supply = mint_state.max_supply;
+        mint_state.lower_max_supply(new_max_supply, ctx.accounts.mint.supply)?;
+
+        // Emit event
+        emit!(SupplyCapUpdated {
+            mint: ctx.accounts.mint.key(),
+            old_max_supply,
+            new_max_supply,
+        });
+
+        msg!("Supply cap lowered from {} to {}", old_max_supply, new_max_supply);
+
+        Ok(())
+    }
+
+    /// Raise the timelock on authority changes for this mint. The delay can never be
+    /// lowered, so holders can rely on the notice period; while it is still 0, changes
+    /// take effect immediately.
+    pub fn set_authority_delay(ctx: Context<SetAuthorityDelay>, new_delay: i64) -> Result<()> {
+        // Verify mint authority
+        validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &ctx.accounts.mint_authority.key())?;
+
+        let mint_state = &mut ctx.accounts.mint_state;
+        let old_delay = mint_state.authority_delay;
+        mint_state.raise_authority_delay(new_delay)?;
+
+        // Emit event
+        emit!(AuthorityDelayUpdated {
+            mint: ctx.accounts.mint.key(),
+            old_delay,
+            new_delay,
+        });
+
+        msg!("Authority delay raised from {}s to {}s", old_delay, new_delay);
 
         Ok(())
     }
 
     /// Freeze a token account to prevent transfers
-    pub fn freeze_account(ctx: Context<FreezeAccount>) -> Result<()> {
-        // Verify freeze authority
+    pub fn freeze_account(ctx: Context<FreezeAccount>, reason: FreezeReason, case_id: String) -> Result<()> {
+        FreezeRecord::validate_case_id(&case_id)?;
+
+        // Verify freeze authority, or a freezer role when the mint state PDA holds it
+        let program_authority = authorize_freezer(
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_state,
+            ctx.accounts.roles.as_mut(),
+            &ctx.accounts.freeze_authority.key(),
+            1,
+        )?;
+
+        // Verify token account belongs to this mint
+        require!(
+            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
+        );
+
+        // Freeze the account
+        let authority = if program_authority {
+            ctx.accounts.mint_state.to_account_info()
+        } else {
+            ctx.accounts.freeze_authority.to_account_info()
+        };
+        let cpi_accounts = SplFreezeAccount {
+            account: ctx.accounts.token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority,
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::freeze_account(cpi_ctx)?;
+
+        // Record why the account was frozen. A stale record keeps its original payer.
+        let payer = match ctx.accounts.freeze_record.payer {
+            payer if payer == Pubkey::default() => ctx.accounts.freeze_authority.key(),
+            payer => payer,
+        };
+        ctx.accounts.freeze_record.set_inner(FreezeRecord {
+            mint: mint_key,
+            account: ctx.accounts.token_account.key(),
+            reason,
+            case_id: case_id.clone(),
+            frozen_at: Clock::get()?.unix_timestamp,
+            frozen_by: ctx.accounts.freeze_authority.key(),
+            payer,
+            bump: ctx.bumps.freeze_record,
+        });
+
+        // Emit event
+        emit!(AccountFrozen {
+            mint: ctx.accounts.mint.key(),
+            account: ctx.accounts.token_account.key(),
+            reason,
+            case_id,
+        });
+
+        msg!("Frozen token account: {} ({:?})", ctx.accounts.token_account.key(), reason);
+
+        Ok(())
+    }
+
+    /// Freeze every token account in `remaining_accounts`, skipping those already
+    /// frozen. Each frozen account gets a freeze record with `reason` and `case_id`.
+    pub fn batch_freeze_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchFreezeAccounts<'info>>,
+        reason: FreezeReason,
+        case_id: String,
+    ) -> Result<BatchFreezeResult> {
+        FreezeRecord::validate_case_id(&case_id)?;
+        set_accounts_frozen(ctx, Some((reason, case_id)))
+    }
+
+    /// Thaw every token account in `remaining_accounts`, skipping those not
+    /// frozen, and close their freeze records, refunding each record's payer
+    pub fn batch_thaw_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchFreezeAccounts<'info>>,
+    ) -> Result<BatchFreezeResult> {
+        set_accounts_frozen(ctx, None)
+    }
+
+    /// Set or clear the compliance authority that manages the denylist
+    pub fn set_compliance_authority(
+        ctx: Context<SetComplianceAuthority>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        // Verify mint authority
+        validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &ctx.accounts.mint_authority.key())?;
+
+        let mint_state = &mut ctx.accounts.mint_state;
+        let old_authority = mint_state.compliance_authority;
+        mint_state.compliance_authority = new_authority;
+
+        // Emit event
+        emit!(ComplianceAuthorityUpdated {
+            mint: ctx.accounts.mint.key(),
+            old_authority,
+            new_authority,
+        });
+
+        msg!("Compliance authority set to: {:?}", new_authority);
+
+        Ok(())
+    }
+
+    /// Block `wallet` from sending or receiving the mint's tokens. With
+    /// `freeze_ata`, its token account is also frozen with a sanctions record.
+    pub fn add_to_denylist(
+        ctx: Context<AddToDenylist>,
+        wallet: Pubkey,
+        case_id: String,
+        freeze_ata: bool,
+    ) -> Result<()> {
+        FreezeRecord::validate_case_id(&case_id)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let mint_key = ctx.accounts.mint.key();
+        ctx.accounts.denylist_entry.set_inner(DenylistEntry {
+            mint: mint_key,
+            wallet,
+            case_id: case_id.clone(),
+            added_by: ctx.accounts.compliance_authority.key(),
+            added_at: now,
+            bump: ctx.bumps.denylist_entry,
+        });
+
+        // Auto-freeze the wallet's token account unless it is already frozen
+        let mut frozen = false;
+        if freeze_ata {
+            let token_account = ctx
+                .accounts
+                .wallet_token_account
+                .as_ref()
+                .ok_or(TokenError::AccountNotInitialized)?;
+            let freeze_record = ctx.accounts.freeze_record.as_ref().ok_or(TokenError::AccountNotInitialized)?;
+            require!(token_account.owner == wallet, TokenError::InvalidOwner);
+            require!(token_account.mint == mint_key, TokenError::MintMismatch);
+            let (record_key, record_bump) =
+                Pubkey::find_program_address(&[FREEZE_RECORD_SEED, token_account.key().as_ref()], &crate::ID);
+            require!(freeze_record.key() == record_key, TokenError::InvalidFreezeRecord);
+
+            if !token_account.is_frozen() {
+                // The program signs when the mint state PDA is the freeze authority
+                let freeze_authority = Option::<Pubkey>::from(ctx.accounts.mint.freeze_authority);
+                let authority = if freeze_authority == Some(ctx.accounts.mint_state.key()) {
+                    ctx.accounts.mint_state.to_account_info()
+                } else {
+                    validate_freeze_authority(&ctx.accounts.mint, &ctx.accounts.compliance_authority.key())?;
+                    ctx.accounts.compliance_authority.to_account_info()
+                };
+                let cpi_accounts = SplFreezeAccount {
+                    account: token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    authority,
+                };
+
+                let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+                token_interface::freeze_account(cpi_ctx)?;
+
+                write_freeze_record(
+                    &freeze_record.to_account_info(),
+                    &ctx.accounts.compliance_authority.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    record_bump,
+                    FreezeRecord {
+                        mint: mint_key,
+                        account: token_account.key(),
+                        reason: FreezeReason::Sanctions,
+                        case_id: case_id.clone(),
+                        frozen_at: now,
+                        frozen_by: ctx.accounts.compliance_authority.key(),
+                        payer: ctx.accounts.compliance_authority.key(),
+                        bump: record_bump,
+                    },
+                )?;
+
+                // Emit event
+                emit!(AccountFrozen {
+                    mint: mint_key,
+                    account: token_account.key(),
+                    reason: FreezeReason::Sanctions,
+                    case_id: case_id.clone(),
+                });
+                frozen = true;
+            }
+        }
+
+        // Emit event
+        emit!(WalletDenylisted {
+            mint: mint_key,
+            wallet,
+            case_id,
+            frozen,
+        });
+
+        msg!("Denylisted wallet: {}", wallet);
+
+        Ok(())
+    }
+
+    /// Lift the denylist block on a wallet. Its token account stays frozen until thawed.
+    pub fn remove_from_denylist(ctx: Context<RemoveFromDenylist>) -> Result<()> {
+        // Emit event
+        emit!(WalletRemovedFromDenylist {
+            mint: ctx.accounts.mint.key(),
+            wallet: ctx.accounts.denylist_entry.wallet,
+        });
+
+        msg!("Removed wallet from denylist: {}", ctx.accounts.denylist_entry.wallet);
+
+        Ok(())
+    }
+
+    /// Choose whether new token accounts start frozen. Token-2022 mints update their
+    /// DefaultAccountState extension as well. Either way the accounts this program
+    /// creates are delivered to and then kept frozen, which needs the mint state
+    /// PDA to hold the freeze authority while the setting is on.
+    pub fn set_default_account_state(ctx: Context<SetDefaultAccountState>, frozen: bool) -> Result<()> {
+        // Verify freeze authority, or the mint authority when the mint state PDA holds it
+        let freeze_authority = Option::<Pubkey>::from(ctx.accounts.mint.freeze_authority);
+        require!(freeze_authority.is_some(), TokenError::FreezeAuthorityRequired);
+        let program_authority = freeze_authority == Some(ctx.accounts.mint_state.key());
+        if program_authority {
+            validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &ctx.accounts.authority.key())?;
+        } else {
+            validate_freeze_authority(&ctx.accounts.mint, &ctx.accounts.authority.key())?;
+        }
+        require!(!frozen || program_authority, TokenError::FreezeAuthorityRequired);
+
+        if ctx.accounts.token_program.key() == anchor_spl::token_2022::ID {
+            {
+                let mint_info = ctx.accounts.mint.to_account_info();
+                let data = mint_info.try_borrow_data()?;
+                let mint = StateWithExtensions::<SplMint>::unpack(&data)?;
+                require!(
+                    mint.get_extension::<DefaultAccountState>().is_ok(),
+                    TokenError::DefaultAccountStateUnsupported
+                );
+            }
+
+            let authority = if program_authority {
+                ctx.accounts.mint_state.to_account_info()
+            } else {
+                ctx.accounts.authority.to_account_info()
+            };
+            let cpi_accounts = DefaultAccountStateUpdate {
+                token_program_id: ctx.accounts.token_program.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                freeze_authority: authority,
+            };
+
+            let mint_key = ctx.accounts.mint.key();
+            let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            let state = if frozen { AccountState::Frozen } else { AccountState::Initialized };
+            default_account_state_update(cpi_ctx, &state)?;
+        }
+
+        ctx.accounts.mint_state.default_frozen = frozen;
+
+        // Emit event
+        emit!(DefaultAccountStateUpdated {
+            mint: ctx.accounts.mint.key(),
+            frozen,
+        });
+
+        msg!("Accounts start frozen: {}", frozen);
+
+        Ok(())
+    }
+
+    /// Require a valid KYC attestation for every wallet receiving the mint's tokens
+    pub fn set_allowlist_required(ctx: Context<SetAllowlistRequired>, required: bool) -> Result<()> {
+        ctx.accounts.mint_state.kyc_required = required;
+
+        // Emit event
+        emit!(AllowlistRequirementUpdated {
+            mint: ctx.accounts.mint.key(),
+            required,
+        });
+
+        msg!("KYC allowlist required: {}", required);
+
+        Ok(())
+    }
+
+    /// Allow `attester` to issue KYC attestations for the mint
+    pub fn add_attester(ctx: Context<ManageAttesters>, attester: Pubkey) -> Result<()> {
+        let mint_key = ctx.accounts.mint.key();
+        let kyc_config = &mut ctx.accounts.kyc_config;
+        kyc_config.mint = mint_key;
+        kyc_config.bump = ctx.bumps.kyc_config;
+        kyc_config.add(attester)?;
+
+        // Emit event
+        emit!(AttesterAdded {
+            mint: mint_key,
+            attester,
+        });
+
+        msg!("Added KYC attester: {}", attester);
+
+        Ok(())
+    }
+
+    /// Stop `attester` from issuing attestations. Attestations it already issued
+    /// stay valid until they expire or are revoked.
+    pub fn remove_attester(ctx: Context<ManageAttesters>, attester: Pubkey) -> Result<()> {
+        ctx.accounts.kyc_config.remove(&attester)?;
+
+        // Emit event
+        emit!(AttesterRemoved {
+            mint: ctx.accounts.mint.key(),
+            attester,
+        });
+
+        msg!("Removed KYC attester: {}", attester);
+
+        Ok(())
+    }
+
+    /// Issue or renew `wallet`'s KYC attestation, valid until `expires_at`. Only
+    /// the attester that issued an attestation can renew it.
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        wallet: Pubkey,
+        jurisdiction: [u8; 2],
+        expires_at: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        Attestation::validate(&jurisdiction, expires_at, now)?;
+
+        let mint_key = ctx.accounts.mint.key();
+        ctx.accounts.attestation.set_inner(Attestation {
+            mint: mint_key,
+            wallet,
+            attester: ctx.accounts.attester.key(),
+            jurisdiction,
+            issued_at: now,
+            expires_at,
+            bump: ctx.bumps.attestation,
+        });
+
+        // Emit event
+        emit!(AttestationIssued {
+            mint: mint_key,
+            wallet,
+            attester: ctx.accounts.attester.key(),
+            jurisdiction,
+            expires_at,
+        });
+
+        msg!("Issued KYC attestation for: {}", wallet);
+
+        Ok(())
+    }
+
+    /// Revoke a wallet's KYC attestation. Signed by its attester or the compliance authority.
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        let revoker = ctx.accounts.revoker.key();
         require!(
-            ctx.accounts.mint.freeze_authority.is_some(),
+            revoker == ctx.accounts.attestation.attester
+                || ctx.accounts.mint_state.compliance_authority == Some(revoker),
             TokenError::Unauthorized
         );
+
+        // Emit event
+        emit!(AttestationRevoked {
+            mint: ctx.accounts.mint.key(),
+            wallet: ctx.accounts.attestation.wallet,
+            revoked_by: revoker,
+        });
+
+        msg!("Revoked KYC attestation for: {}", ctx.accounts.attestation.wallet);
+
+        Ok(())
+    }
+
+    /// Get the freeze record explaining why a token account is frozen
+    pub fn get_freeze_record(ctx: Context<GetFreezeRecord>) -> Result<FreezeRecord> {
+        Ok(ctx.accounts.freeze_record.clone().into_inner())
+    }
+
+    /// Thaw a frozen token account to re-enable transfers
+    pub fn thaw_account(ctx: Context<ThawAccount>) -> Result<()> {
+        // Verify freeze authority, or a freezer role when the mint state PDA holds it
+        let program_authority = authorize_freezer(
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_state,
+            ctx.accounts.roles.as_mut(),
+            &ctx.accounts.freeze_authority.key(),
+            1,
+        )?;
+
+        // Verify token account belongs to this mint
+        require!(
+            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
+        );
+
+        // Thaw the account
+        let authority = if program_authority {
+            ctx.accounts.mint_state.to_account_info()
+        } else {
+            ctx.accounts.freeze_authority.to_account_info()
+        };
+        let cpi_accounts = SplThawAccount {
+            account: ctx.accounts.token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority,
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::thaw_account(cpi_ctx)?;
+
+        // Close the freeze record, refunding its rent to whoever paid it
+        close_freeze_record(
+            &ctx.accounts.freeze_record.to_account_info(),
+            ctx.accounts.record_payer.as_ref().map(|payer| payer.to_account_info()),
+        )?;
+
+        // Emit event
+        emit!(AccountThawed {
+            mint: ctx.accounts.mint.key(),
+            account: ctx.accounts.token_account.key(),
+        });
+
+        msg!("Thawed token account: {}", ctx.accounts.token_account.key());
+
+        Ok(())
+    }
+
+    /// Revoke the freeze authority, or hand it to the mint's multisig.
+    /// Any other change goes through `propose_authority` / `accept_authority`.
+    /// While the mint state PDA holds the freeze authority, the mint authority or
+    /// an admin role holder acts for it.
+    pub fn set_freeze_authority(
+        ctx: Context<SetMintAuthority>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        // Verify current freeze authority
+        let program_authority = authorize_freeze_authority_change(
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_state,
+            ctx.accounts.roles.as_ref(),
+            &ctx.accounts.current_authority.key(),
+        )?;
+
+        let multisig = ctx.accounts.multisig.as_ref().map(|multisig| multisig.key());
+        require!(
+            is_immediate_authority_change(new_authority, ctx.accounts.mint_state.key(), multisig),
+            TokenError::AuthorityChangeRequiresAcceptance
+        );
+        ctx.accounts.mint_state.require_no_timelock()?;
+
+        // Set new freeze authority
+        let current_authority = if program_authority {
+            ctx.accounts.mint_state.to_account_info()
+        } else {
+            ctx.accounts.current_authority.to_account_info()
+        };
+        let cpi_accounts = SplSetAuthority {
+            account_or_mint: ctx.accounts.mint.to_account_info(),
+            current_authority,
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::set_authority(cpi_ctx, AuthorityType::FreezeAccount, new_authority)?;
+
+        match new_authority {
+            Some(authority) => {
+                msg!("Freeze authority changed to: {}", authority);
+            }
+            None => {
+                msg!("Freeze authority revoked permanently");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Propose handing the mint or freeze authority to a new key. The authority is
+    /// held in escrow by the pending authority PDA until the new key accepts or
+    /// the proposer cancels.
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        kind: AuthorityKind,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let current_authority = ctx.accounts.current_authority.key();
+        require!(new_authority != current_authority, TokenError::AuthorityAlreadySet);
+        ctx.accounts.mint_state.require_no_timelock()?;
+
+        // Verify current authority. While the mint state PDA holds the authority it
+        // keeps it until the handover is accepted: for the mint authority only the
+        // minting operator changes, and a freeze authority is released on acceptance.
+        let program_authority = match kind {
+            AuthorityKind::Mint => {
+                validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &current_authority)?
+            }
+            AuthorityKind::Freeze => authorize_freeze_authority_change(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                ctx.accounts.roles.as_ref(),
+                &current_authority,
+            )?,
+        };
+
+        // Move the authority into escrow
+        let escrowed = !program_authority;
+        if escrowed {
+            let cpi_accounts = SplSetAuthority {
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+                current_authority: ctx.accounts.current_authority.to_account_info(),
+            };
+
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            token_interface::set_authority(
+                cpi_ctx,
+                kind.into(),
+                Some(ctx.accounts.pending_authority.key()),
+            )?;
+        }
+
+        ctx.accounts.pending_authority.set_inner(PendingAuthority {
+            mint: ctx.accounts.mint.key(),
+            kind,
+            proposer: current_authority,
+            new_authority,
+            escrowed,
+            proposed_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.pending_authority,
+        });
+
+        // Emit event
+        emit!(AuthorityChangeProposed {
+            mint: ctx.accounts.mint.key(),
+            kind,
+            current_authority,
+            new_authority,
+        });
+
+        msg!("Proposed {:?} authority handover to: {}", kind, new_authority);
+
+        Ok(())
+    }
+
+    /// Accept a proposed authority handover (signed by the proposed key)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let pending = &ctx.accounts.pending_authority;
+        let (kind, new_authority) = (pending.kind, pending.new_authority);
+
+        if pending.escrowed {
+            release_escrowed_authority(
+                &ctx.accounts.mint,
+                &pending.to_account_info(),
+                pending,
+                &ctx.accounts.token_program,
+                new_authority,
+            )?;
+        } else if kind == AuthorityKind::Freeze {
+            // The mint state PDA held the freeze authority throughout
+            let cpi_accounts = SplSetAuthority {
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+                current_authority: ctx.accounts.mint_state.to_account_info(),
+            };
+
+            let mint_key = ctx.accounts.mint.key();
+            let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            token_interface::set_authority(cpi_ctx, AuthorityType::FreezeAccount, Some(new_authority))?;
+        }
+        if kind == AuthorityKind::Mint {
+            ctx.accounts.mint_state.mint_authority = new_authority;
+        }
+
+        // Emit event
+        emit!(AuthorityChangeAccepted {
+            mint: ctx.accounts.mint.key(),
+            kind,
+            old_authority: pending.proposer,
+            new_authority,
+        });
+
+        msg!("{:?} authority changed to: {}", kind, new_authority);
+
+        Ok(())
+    }
+
+    /// Cancel a proposed authority handover, returning the authority to the proposer
+    pub fn cancel_authority(ctx: Context<CancelAuthority>) -> Result<()> {
+        let pending = &ctx.accounts.pending_authority;
+
+        if pending.escrowed {
+            release_escrowed_authority(
+                &ctx.accounts.mint,
+                &pending.to_account_info(),
+                pending,
+                &ctx.accounts.token_program,
+                pending.proposer,
+            )?;
+        }
+
+        // Emit event
+        emit!(AuthorityChangeCancelled {
+            mint: ctx.accounts.mint.key(),
+            kind: pending.kind,
+            proposer: pending.proposer,
+            new_authority: pending.new_authority,
+        });
+
+        msg!("Cancelled {:?} authority handover to: {}", pending.kind, pending.new_authority);
+
+        Ok(())
+    }
+
+    /// Announce a mint or freeze authority change (or revocation) on a timelocked
+    /// mint. It can be executed once the mint's authority delay has passed.
+    pub fn queue_authority_change(
+        ctx: Context<QueueAuthorityChange>,
+        kind: AuthorityKind,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        let current_authority = ctx.accounts.current_authority.key();
+        require!(new_authority != Some(current_authority), TokenError::AuthorityAlreadySet);
+
+        // Verify current authority
+        match kind {
+            AuthorityKind::Mint => {
+                validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &current_authority)?;
+            }
+            AuthorityKind::Freeze => {
+                authorize_freeze_authority_change(
+                    &ctx.accounts.mint,
+                    &ctx.accounts.mint_state,
+                    ctx.accounts.roles.as_ref(),
+                    &current_authority,
+                )?;
+            }
+        }
+
+        let execute_after = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.mint_state.authority_delay)
+            .ok_or(TokenError::InvalidAuthorityDelay)?;
+
+        ctx.accounts.queued_change.set_inner(QueuedAuthorityChange {
+            mint: ctx.accounts.mint.key(),
+            kind,
+            new_authority,
+            queued_by: current_authority,
+            execute_after,
+            bump: ctx.bumps.queued_change,
+        });
+
+        // Emit event
+        emit!(AuthorityChangeQueued {
+            mint: ctx.accounts.mint.key(),
+            kind,
+            current_authority,
+            new_authority,
+            execute_after,
+        });
+
+        msg!("Queued {:?} authority change, executable after {}", kind, execute_after);
+
+        Ok(())
+    }
+
+    /// Execute a queued authority change once its timelock has passed. A new key
+    /// other than the mint's multisig must co-sign, as with `accept_authority`.
+    pub fn execute_authority_change(ctx: Context<ExecuteAuthorityChange>) -> Result<()> {
+        let queued = &ctx.accounts.queued_change;
+        let (kind, new_authority) = (queued.kind, queued.new_authority);
+        let current_authority = ctx.accounts.current_authority.key();
+
+        require!(
+            queued.is_ready(Clock::get()?.unix_timestamp),
+            TokenError::TimelockNotExpired
+        );
+
+        // The queuing key must still hold the authority
+        let program_authority = match kind {
+            AuthorityKind::Mint => {
+                validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &current_authority)?
+            }
+            AuthorityKind::Freeze => authorize_freeze_authority_change(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                ctx.accounts.roles.as_ref(),
+                &current_authority,
+            )?,
+        };
+
+        let multisig = ctx.accounts.multisig.as_ref().map(|multisig| multisig.key());
+        if !is_immediate_authority_change(new_authority, ctx.accounts.mint_state.key(), multisig) {
+            let signer = ctx.accounts.new_authority.as_ref().map(|signer| signer.key());
+            require!(signer == new_authority, TokenError::AuthorityChangeRequiresAcceptance);
+        }
+
+        if let (AuthorityKind::Mint, true, Some(operator)) = (kind, program_authority, new_authority) {
+            // Program authority mode only rotates the minting operator
+            ctx.accounts.mint_state.mint_authority = operator;
+        } else {
+            let authority = if program_authority {
+                ctx.accounts.mint_state.to_account_info()
+            } else {
+                ctx.accounts.current_authority.to_account_info()
+            };
+            let cpi_accounts = SplSetAuthority {
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+                current_authority: authority,
+            };
+
+            let mint_key = ctx.accounts.mint.key();
+            let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            token_interface::set_authority(cpi_ctx, kind.into(), new_authority)?;
+            if kind == AuthorityKind::Mint {
+                ctx.accounts.mint_state.mint_authority = new_authority.unwrap_or_default();
+            }
+        }
+
+        // Emit event
+        emit!(QueuedAuthorityChangeExecuted {
+            mint: ctx.accounts.mint.key(),
+            kind,
+            old_authority: current_authority,
+            new_authority,
+        });
+
+        match new_authority {
+            Some(authority) => msg!("{:?} authority changed to: {}", kind, authority),
+            None => msg!("{:?} authority revoked permanently", kind),
+        }
+
+        Ok(())
+    }
+
+    /// Cancel a queued authority change before it executes
+    pub fn cancel_authority_change(ctx: Context<CancelAuthorityChange>) -> Result<()> {
+        let queued = &ctx.accounts.queued_change;
+
+        // Emit event
+        emit!(QueuedAuthorityChangeCancelled {
+            mint: ctx.accounts.mint.key(),
+            kind: queued.kind,
+            current_authority: ctx.accounts.current_authority.key(),
+            new_authority: queued.new_authority,
+        });
+
+        msg!("Cancelled queued {:?} authority change", queued.kind);
+
+        Ok(())
+    }
+
+    /// Create the role registry for a mint, making the mint authority its first admin
+    pub fn initialize_roles(ctx: Context<InitializeRoles>) -> Result<()> {
+        // Verify mint authority
+        let admin = ctx.accounts.mint_authority.key();
+        validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &admin)?;
+
+        ctx.accounts.roles.set_inner(Roles {
+            mint: ctx.accounts.mint.key(),
+            grants: vec![RoleGrant {
+                holder: admin,
+                role: Role::Admin,
+                allowance: None,
+            }],
+            bump: ctx.bumps.roles,
+        });
+
+        // Emit event
+        emit!(RoleGranted {
+            mint: ctx.accounts.mint.key(),
+            holder: admin,
+            role: Role::Admin,
+            allowance: None,
+            granted_by: admin,
+        });
+
+        msg!("Roles initialized with admin: {}", admin);
+
+        Ok(())
+    }
+
+    /// Grant a role (admin only). Granting an existing role replaces its allowance.
+    pub fn grant_role(
+        ctx: Context<UpdateRole>,
+        holder: Pubkey,
+        role: Role,
+        allowance: Option<u64>,
+    ) -> Result<()> {
+        let admin = ctx.accounts.admin.key();
+        let roles = &mut ctx.accounts.roles;
+        require!(roles.has_role(&admin, Role::Admin), TokenError::MissingRole);
+
+        roles.grant(holder, role, allowance)?;
+
+        // Emit event
+        emit!(RoleGranted {
+            mint: ctx.accounts.mint.key(),
+            holder,
+            role,
+            allowance,
+            granted_by: admin,
+        });
+
+        msg!("Granted {:?} role to: {}", role, holder);
+
+        Ok(())
+    }
+
+    /// Revoke a role (admin only). The last admin cannot be revoked.
+    pub fn revoke_role(ctx: Context<UpdateRole>, holder: Pubkey, role: Role) -> Result<()> {
+        let admin = ctx.accounts.admin.key();
+        let roles = &mut ctx.accounts.roles;
+        require!(roles.has_role(&admin, Role::Admin), TokenError::MissingRole);
+
+        roles.revoke(&holder, role)?;
+
+        // Emit event
+        emit!(RoleRevoked {
+            mint: ctx.accounts.mint.key(),
+            holder,
+            role,
+            revoked_by: admin,
+        });
+
+        msg!("Revoked {:?} role from: {}", role, holder);
+
+        Ok(())
+    }
+
+    /// Pause minting, transfers and burns through the program (pauser role only)
+    pub fn pause_mint(ctx: Context<PauseMint>) -> Result<()> {
+        ctx.accounts
+            .roles
+            .consume(&ctx.accounts.pauser.key(), Role::Pauser, 1)?;
+
+        ctx.accounts.mint_state.paused = true;
+
+        // Emit event
+        emit!(MintPaused {
+            mint: ctx.accounts.mint.key(),
+            pauser: ctx.accounts.pauser.key(),
+        });
+
+        msg!("Mint paused: {}", ctx.accounts.mint.key());
+
+        Ok(())
+    }
+
+    /// Resume a paused mint (pauser role only)
+    pub fn unpause_mint(ctx: Context<PauseMint>) -> Result<()> {
+        ctx.accounts
+            .roles
+            .consume(&ctx.accounts.pauser.key(), Role::Pauser, 1)?;
+
+        ctx.accounts.mint_state.paused = false;
+
+        // Emit event
+        emit!(MintUnpaused {
+            mint: ctx.accounts.mint.key(),
+            pauser: ctx.accounts.pauser.key(),
+        });
+
+        msg!("Mint unpaused: {}", ctx.accounts.mint.key());
+
+        Ok(())
+    }
+
+    /// Create the multisig PDA for a mint (signed by its mint or freeze authority).
+    /// Hand it the mint or freeze authority afterwards with the set-authority instructions.
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        // Only a current authority of the mint may set up its multisig
+        let authority = ctx.accounts.authority.key();
         require!(
-            ctx.accounts.mint.freeze_authority.unwrap() == ctx.accounts.freeze_authority.key(),
+            validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &authority).is_ok()
+                || validate_freeze_authority(&ctx.accounts.mint, &authority).is_ok(),
             TokenError::Unauthorized
         );
 
-        // Verify token account belongs to this mint
+        Multisig::validate_config(&signers, threshold)?;
+
+        ctx.accounts.multisig.set_inner(Multisig {
+            mint: ctx.accounts.mint.key(),
+            signers: signers.clone(),
+            threshold,
+            proposal_count: 0,
+            bump: ctx.bumps.multisig,
+        });
+
+        // Emit event
+        emit!(MultisigCreated {
+            mint: ctx.accounts.mint.key(),
+            multisig: ctx.accounts.multisig.key(),
+            signers,
+            threshold,
+        });
+
+        msg!("Multisig created: {} (threshold {})", ctx.accounts.multisig.key(), threshold);
+
+        Ok(())
+    }
+
+    /// Propose a mint or freeze authority operation; the proposer's approval is counted
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: MultisigAction) -> Result<()> {
+        action.validate()?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.require_signer(&ctx.accounts.proposer.key())?;
+
+        let index = multisig.proposal_count;
+        multisig.proposal_count = index.checked_add(1).ok_or(TokenError::InvalidMultisigConfig)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.set_inner(MultisigProposal {
+            multisig: multisig.key(),
+            index,
+            proposer: ctx.accounts.proposer.key(),
+            action: action.clone(),
+            approvals: Vec::new(),
+            executed: false,
+            bump: ctx.bumps.proposal,
+        });
+        let approvals = proposal.approve(multisig, &ctx.accounts.proposer.key())?;
+
+        // Emit events
+        emit!(ProposalCreated {
+            multisig: multisig.key(),
+            proposal: proposal.key(),
+            index,
+            proposer: ctx.accounts.proposer.key(),
+            action,
+        });
+        emit!(ProposalApproved {
+            multisig: multisig.key(),
+            proposal: proposal.key(),
+            signer: ctx.accounts.proposer.key(),
+            approvals,
+        });
+
+        msg!("Proposal {} created", index);
+
+        Ok(())
+    }
+
+    /// Approve a pending multisig proposal
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let approvals = ctx
+            .accounts
+            .proposal
+            .approve(&ctx.accounts.multisig, &ctx.accounts.signer.key())?;
+
+        // Emit event
+        emit!(ProposalApproved {
+            multisig: ctx.accounts.multisig.key(),
+            proposal: ctx.accounts.proposal.key(),
+            signer: ctx.accounts.signer.key(),
+            approvals,
+        });
+
+        msg!(
+            "Proposal {} approved ({}/{})",
+            ctx.accounts.proposal.index,
+            approvals,
+            ctx.accounts.multisig.threshold
+        );
+
+        Ok(())
+    }
+
+    /// Execute an approved multisig proposal, signing with the multisig PDA
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &ctx.accounts.proposal;
+        let action = proposal.action.clone();
+
+        // Verify the proposal can run
+        multisig.require_signer(&ctx.accounts.executor.key())?;
+        require!(!proposal.executed, TokenError::ProposalAlreadyExecuted);
+        require!(proposal.is_approved(multisig), TokenError::ThresholdNotMet);
+
+        // The optional token account must be the one named in the action
+        let token_account = ctx.accounts.token_account.as_ref();
         require!(
-            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
-            TokenError::MintMismatch
+            token_account.map(|account| account.key()) == action.token_account(),
+            TokenError::InvalidProposalAccounts
         );
+        if let Some(token_account) = token_account {
+            require!(
+                token_account.mint == ctx.accounts.mint.key(),
+                TokenError::MintMismatch
+            );
+        }
 
-        // Freeze the account
-        let cpi_accounts = SplFreezeAccount {
-            account: ctx.accounts.token_account.to_account_info(),
+        let mint_key = ctx.accounts.mint.key();
+        let multisig_key = multisig.key();
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[MULTISIG_SEED, mint_key.as_ref(), &[multisig.bump]],
+            &[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]],
+        ];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        match action {
+            MultisigAction::MintTokens { amount, .. } => {
+                ctx.accounts.mint_state.require_not_paused()?;
+                let program_authority =
+                    validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &multisig_key)?;
+                ctx.accounts.mint_state.check_supply_cap(ctx.accounts.mint.supply, amount)?;
+                enforce_rate_limit(
+                    &ctx.accounts.mint_state,
+                    ctx.accounts.rate_limit.as_mut(),
+                    &multisig_key,
+                    amount,
+                )?;
+
+                let authority = if program_authority {
+                    ctx.accounts.mint_state.to_account_info()
+                } else {
+                    ctx.accounts.multisig.to_account_info()
+                };
+                let destination = token_account.unwrap();
+                if ctx.accounts.mint_state.compliance_authority.is_some() {
+                    let entry = ctx
+                        .accounts
+                        .recipient_denylist_entry
+                        .as_ref()
+                        .ok_or(TokenError::AccountNotInitialized)?;
+                    require_not_denylisted_wallet(&mint_key, &destination.owner, entry)?;
+                }
+                if ctx.accounts.mint_state.kyc_required {
+                    let attestation = ctx
+                        .accounts
+                        .recipient_attestation
+                        .as_ref()
+                        .ok_or(TokenError::AttestationMissing)?;
+                    require_attested_wallet(&mint_key, &destination.owner, attestation, Clock::get()?.unix_timestamp)?;
+                }
+                let cpi_accounts = SplMintToChecked {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: destination.to_account_info(),
+                    authority,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+                token_interface::mint_to_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+                ctx.accounts.mint_state.record_mint(amount)?;
+
+                emit!(TokensMinted {
+                    mint: mint_key,
+                    recipient: destination.key(),
+                    amount,
+                });
+            }
+            MultisigAction::SetMintAuthority { new_authority } => {
+                require!(
+                    is_immediate_authority_change(new_authority, ctx.accounts.mint_state.key(), Some(multisig_key)),
+                    TokenError::AuthorityChangeRequiresAcceptance
+                );
+                ctx.accounts.mint_state.require_no_timelock()?;
+                let program_authority = validate_multisig_authority(
+                    &ctx.accounts.mint,
+                    &ctx.accounts.mint_state,
+                    &multisig_key,
+                    AuthorityKind::Mint,
+                )?;
+                set_multisig_held_authority(ctx.accounts, AuthorityKind::Mint, new_authority, program_authority, signer_seeds)?;
+            }
+            MultisigAction::FreezeAccount { reason, ref case_id, .. } => {
+                validate_freeze_authority(&ctx.accounts.mint, &multisig_key)?;
+
+                let account = token_account.unwrap();
+                let (record, record_bump) = freeze_record_account(ctx.accounts.freeze_record.as_ref(), &account.key())?;
+                let system_program = ctx
+                    .accounts
+                    .system_program
+                    .as_ref()
+                    .ok_or(TokenError::InvalidProposalAccounts)?;
+                let cpi_accounts = SplFreezeAccount {
+                    account: account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    authority: ctx.accounts.multisig.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+                token_interface::freeze_account(cpi_ctx)?;
+
+                write_freeze_record(
+                    record,
+                    &ctx.accounts.executor.to_account_info(),
+                    &system_program.to_account_info(),
+                    record_bump,
+                    FreezeRecord {
+                        mint: mint_key,
+                        account: account.key(),
+                        reason,
+                        case_id: case_id.clone(),
+                        frozen_at: Clock::get()?.unix_timestamp,
+                        frozen_by: multisig_key,
+                        payer: ctx.accounts.executor.key(),
+                        bump: record_bump,
+                    },
+                )?;
+
+                // Emit event
+                emit!(AccountFrozen {
+                    mint: mint_key,
+                    account: account.key(),
+                    reason,
+                    case_id: case_id.clone(),
+                });
+            }
+            MultisigAction::ThawAccount { .. } => {
+                validate_freeze_authority(&ctx.accounts.mint, &multisig_key)?;
+
+                let account = token_account.unwrap();
+                let (record, _) = freeze_record_account(ctx.accounts.freeze_record.as_ref(), &account.key())?;
+                let cpi_accounts = SplThawAccount {
+                    account: account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    authority: ctx.accounts.multisig.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+                token_interface::thaw_account(cpi_ctx)?;
+
+                close_freeze_record(record, ctx.accounts.record_payer.as_ref().map(|payer| payer.to_account_info()))?;
+
+                // Emit event
+                emit!(AccountThawed {
+                    mint: mint_key,
+                    account: account.key(),
+                });
+            }
+            MultisigAction::SetFreezeAuthority { new_authority } => {
+                require!(
+                    is_immediate_authority_change(new_authority, ctx.accounts.mint_state.key(), Some(multisig_key)),
+                    TokenError::AuthorityChangeRequiresAcceptance
+                );
+                ctx.accounts.mint_state.require_no_timelock()?;
+                let program_authority = validate_multisig_authority(
+                    &ctx.accounts.mint,
+                    &ctx.accounts.mint_state,
+                    &multisig_key,
+                    AuthorityKind::Freeze,
+                )?;
+                set_multisig_held_authority(ctx.accounts, AuthorityKind::Freeze, new_authority, program_authority, signer_seeds)?;
+            }
+            MultisigAction::QueueAuthorityChange { kind, new_authority } => {
+                require!(new_authority != Some(multisig_key), TokenError::AuthorityAlreadySet);
+                validate_multisig_authority(&ctx.accounts.mint, &ctx.accounts.mint_state, &multisig_key, kind)?;
+
+                let (queued_change, bump) = queued_change_account(ctx.accounts.queued_change.as_ref(), &mint_key, kind)?;
+                require!(!is_initialized_pda(queued_change), TokenError::AuthorityChangeAlreadyQueued);
+                let system_program = ctx
+                    .accounts
+                    .system_program
+                    .as_ref()
+                    .ok_or(TokenError::InvalidProposalAccounts)?;
+
+                let execute_after = Clock::get()?
+                    .unix_timestamp
+                    .checked_add(ctx.accounts.mint_state.authority_delay)
+                    .ok_or(TokenError::InvalidAuthorityDelay)?;
+
+                create_program_account(
+                    queued_change,
+                    &ctx.accounts.executor.to_account_info(),
+                    &system_program.to_account_info(),
+                    &[QUEUED_CHANGE_SEED, mint_key.as_ref(), kind.seed(), &[bump]],
+                    QueuedAuthorityChange::LEN,
+                )?;
+                let mut buffer = queued_change.try_borrow_mut_data()?;
+                QueuedAuthorityChange {
+                    mint: mint_key,
+                    kind,
+                    new_authority,
+                    queued_by: multisig_key,
+                    execute_after,
+                    bump,
+                }
+                .try_serialize(&mut &mut buffer[..])?;
+
+                emit!(AuthorityChangeQueued {
+                    mint: mint_key,
+                    kind,
+                    current_authority: multisig_key,
+                    new_authority,
+                    execute_after,
+                });
+            }
+            MultisigAction::ExecuteAuthorityChange { kind } => {
+                let (queued_change, _) = queued_change_account(ctx.accounts.queued_change.as_ref(), &mint_key, kind)?;
+                let queued_change = queued_change.clone();
+                let queued = load_multisig_queued_change(&queued_change, &multisig_key)?;
+                require!(
+                    queued.is_ready(Clock::get()?.unix_timestamp),
+                    TokenError::TimelockNotExpired
+                );
+
+                // The multisig must still hold the authority
+                let program_authority =
+                    validate_multisig_authority(&ctx.accounts.mint, &ctx.accounts.mint_state, &multisig_key, kind)?;
+                if !is_immediate_authority_change(queued.new_authority, ctx.accounts.mint_state.key(), Some(multisig_key)) {
+                    let signer = ctx.accounts.new_authority.as_ref().map(|signer| signer.key());
+                    require!(signer == queued.new_authority, TokenError::AuthorityChangeRequiresAcceptance);
+                }
+
+                set_multisig_held_authority(ctx.accounts, kind, queued.new_authority, program_authority, signer_seeds)?;
+                close_program_account(&queued_change, &ctx.accounts.executor.to_account_info())?;
+
+                emit!(QueuedAuthorityChangeExecuted {
+                    mint: mint_key,
+                    kind,
+                    old_authority: multisig_key,
+                    new_authority: queued.new_authority,
+                });
+            }
+            MultisigAction::CancelAuthorityChange { kind } => {
+                let (queued_change, _) = queued_change_account(ctx.accounts.queued_change.as_ref(), &mint_key, kind)?;
+                let queued = load_multisig_queued_change(queued_change, &multisig_key)?;
+                close_program_account(queued_change, &ctx.accounts.executor.to_account_info())?;
+
+                emit!(QueuedAuthorityChangeCancelled {
+                    mint: mint_key,
+                    kind,
+                    current_authority: multisig_key,
+                    new_authority: queued.new_authority,
+                });
+            }
+            MultisigAction::ProposeAuthority { kind, new_authority } => {
+                require!(new_authority != multisig_key, TokenError::AuthorityAlreadySet);
+                ctx.accounts.mint_state.require_no_timelock()?;
+                let program_authority =
+                    validate_multisig_authority(&ctx.accounts.mint, &ctx.accounts.mint_state, &multisig_key, kind)?;
+
+                let (pending, bump) = pending_authority_account(ctx.accounts.pending_authority.as_ref(), &mint_key, kind)?;
+                require!(!is_initialized_pda(pending), TokenError::AuthorityChangeAlreadyProposed);
+                let system_program = ctx
+                    .accounts
+                    .system_program
+                    .as_ref()
+                    .ok_or(TokenError::InvalidProposalAccounts)?;
+
+                create_program_account(
+                    pending,
+                    &ctx.accounts.executor.to_account_info(),
+                    &system_program.to_account_info(),
+                    &[PENDING_AUTHORITY_SEED, mint_key.as_ref(), kind.seed(), &[bump]],
+                    PendingAuthority::LEN,
+                )?;
+
+                // Move the authority into escrow. In program authority mode only the
+                // minting operator changes, so the mint state PDA keeps the authority.
+                let escrowed = !program_authority;
+                if escrowed {
+                    let cpi_accounts = SplSetAuthority {
+                        account_or_mint: ctx.accounts.mint.to_account_info(),
+                        current_authority: ctx.accounts.multisig.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+                    token_interface::set_authority(cpi_ctx, kind.into(), Some(pending.key()))?;
+                }
+
+                let mut buffer = pending.try_borrow_mut_data()?;
+                PendingAuthority {
+                    mint: mint_key,
+                    kind,
+                    proposer: multisig_key,
+                    new_authority,
+                    escrowed,
+                    proposed_at: Clock::get()?.unix_timestamp,
+                    bump,
+                }
+                .try_serialize(&mut &mut buffer[..])?;
+
+                emit!(AuthorityChangeProposed {
+                    mint: mint_key,
+                    kind,
+                    current_authority: multisig_key,
+                    new_authority,
+                });
+            }
+            MultisigAction::CancelAuthority { kind } => {
+                let (pending_info, _) = pending_authority_account(ctx.accounts.pending_authority.as_ref(), &mint_key, kind)?;
+                require!(is_initialized_pda(pending_info), TokenError::InvalidProposalAccounts);
+                let pending = PendingAuthority::try_deserialize(&mut &pending_info.try_borrow_data()?[..])?;
+                require!(pending.proposer == multisig_key, TokenError::Unauthorized);
+
+                if pending.escrowed {
+                    release_escrowed_authority(&ctx.accounts.mint, pending_info, &pending, &ctx.accounts.token_program, multisig_key)?;
+                }
+                close_program_account(pending_info, &ctx.accounts.executor.to_account_info())?;
+
+                emit!(AuthorityChangeCancelled {
+                    mint: mint_key,
+                    kind,
+                    proposer: multisig_key,
+                    new_authority: pending.new_authority,
+                });
+            }
+        }
+
+        ctx.accounts.proposal.executed = true;
+
+        // Emit event
+        emit!(ProposalExecuted {
+            multisig: multisig_key,
+            proposal: ctx.accounts.proposal.key(),
+            executor: ctx.accounts.executor.key(),
+            action,
+        });
+
+        msg!("Proposal {} executed", ctx.accounts.proposal.index);
+
+        Ok(())
+    }
+
+    /// Create the merkle airdrop for a mint (mint authority only), escrowing
+    /// `total_allocation` tokens from `source` in the distributor's vault
+    pub fn create_distributor(
+        ctx: Context<CreateDistributor>,
+        merkle_root: [u8; 32],
+        total_allocation: u64,
+        num_recipients: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        // Verify mint authority
+        let authority = ctx.accounts.authority.key();
+        validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &authority)?;
+
+        Distributor::validate_config(total_allocation, num_recipients, expires_at, Clock::get()?.unix_timestamp)?;
+
+        // Fund the vault
+        let cpi_accounts = SplTransferChecked {
+            from: ctx.accounts.source.to_account_info(),
             mint: ctx.accounts.mint.to_account_info(),
-            authority: ctx.accounts.freeze_authority.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
         };
 
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        token::freeze_account(cpi_ctx)?;
+        token_interface::transfer_checked(cpi_ctx, total_allocation, ctx.accounts.mint.decimals)?;
+
+        ctx.accounts.distributor.set_inner(Distributor {
+            mint: ctx.accounts.mint.key(),
+            authority,
+            merkle_root,
+            total_allocation,
+            total_claimed: 0,
+            num_recipients,
+            expires_at,
+            bump: ctx.bumps.distributor,
+            claimed: vec![0; Distributor::bitmap_len(num_recipients)],
+        });
 
         // Emit event
-        emit!(AccountFrozen {
+        emit!(DistributorCreated {
             mint: ctx.accounts.mint.key(),
-            account: ctx.accounts.token_account.key(),
+            distributor: ctx.accounts.distributor.key(),
+            merkle_root,
+            total_allocation,
+            num_recipients,
+            expires_at,
         });
 
-        msg!("Frozen token account: {}", ctx.accounts.token_account.key());
+        msg!("Airdrop created for {} recipients ({} tokens)", num_recipients, total_allocation);
 
         Ok(())
     }
 
-    /// Thaw a frozen token account to re-enable transfers
-    pub fn thaw_account(ctx: Context<ThawAccount>) -> Result<()> {
-        // Verify freeze authority
+    /// Claim an airdrop allocation with a merkle proof, before the airdrop expires
+    pub fn claim(ctx: Context<ClaimAirdrop>, index: u64, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        require!(amount > 0, TokenError::InvalidAmount);
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.claimant_denylist_entry.as_ref())?;
+        require_attested(&ctx.accounts.mint_state, ctx.accounts.claimant_attestation.as_ref(), now)?;
+        let distributor = &mut ctx.accounts.distributor;
+        require!(now < distributor.expires_at, TokenError::AirdropExpired);
+        distributor.claim(index, &ctx.accounts.claimant.key(), amount, &proof)?;
+
+        // Create the ATA if it does not exist yet
+        let new_account = create_associated_account(
+            &ctx.accounts.claimant_token_account,
+            &ctx.accounts.claimant,
+            &ctx.accounts.mint,
+            &ctx.accounts.claimant,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+        )?;
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            thaw_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.claimant_token_account,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        // Transfer from the vault, signed by the distributor PDA
+        let cpi_accounts = SplTransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.claimant_token_account.to_account_info(),
+            authority: distributor.to_account_info(),
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[DISTRIBUTOR_SEED, mint_key.as_ref(), &[distributor.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        // Emit event
+        emit!(AirdropClaimed {
+            mint: mint_key,
+            claimant: ctx.accounts.claimant.key(),
+            index,
+            amount,
+        });
+
+        // New accounts of a default-frozen mint stay frozen once delivered to
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            freeze_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.claimant_token_account,
+                ctx.accounts.claimant_freeze_record.as_deref(),
+                &ctx.accounts.claimant,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        msg!("Claimed {} tokens for index {}", amount, index);
+
+        Ok(())
+    }
+
+    /// Return unclaimed tokens to the airdrop authority after expiry and close
+    /// the distributor and its vault. The destination owner must be attested
+    /// while the mint requires an allowlist.
+    pub fn clawback(ctx: Context<ClawbackAirdrop>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let distributor = &ctx.accounts.distributor;
+        require!(now >= distributor.expires_at, TokenError::AirdropNotExpired);
+        require_attested(&ctx.accounts.mint_state, ctx.accounts.destination_attestation.as_ref(), now)?;
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[DISTRIBUTOR_SEED, mint_key.as_ref(), &[distributor.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        let amount = ctx.accounts.vault.amount;
+        if amount > 0 {
+            let cpi_accounts = SplTransferChecked {
+                from: ctx.accounts.vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: distributor.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        }
+
+        let cpi_accounts = SplCloseAccount {
+            account: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: distributor.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::close_account(cpi_ctx)?;
+
+        // Emit event
+        emit!(AirdropClawedBack {
+            mint: mint_key,
+            distributor: distributor.key(),
+            amount,
+        });
+
+        msg!("Clawed back {} unclaimed tokens", amount);
+
+        Ok(())
+    }
+
+    /// Escrow `total_amount` tokens from `source` for a beneficiary on a cliff and
+    /// linear vesting schedule (mint authority only)
+    pub fn create_vesting(
+        ctx: Context<CreateVesting>,
+        total_amount: u64,
+        start_ts: i64,
+        cliff_ts: i64,
+        end_ts: i64,
+        revocable: bool,
+    ) -> Result<()> {
+        // Verify mint authority
+        let authority = ctx.accounts.authority.key();
+        validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &authority)?;
+
+        Vesting::validate_schedule(total_amount, start_ts, cliff_ts, end_ts)?;
+
+        // Fund the vault
+        let cpi_accounts = SplTransferChecked {
+            from: ctx.accounts.source.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        token_interface::transfer_checked(cpi_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        let beneficiary = ctx.accounts.beneficiary.key();
+        ctx.accounts.vesting.set_inner(Vesting {
+            mint: ctx.accounts.mint.key(),
+            authority,
+            beneficiary,
+            total_amount,
+            released: 0,
+            start_ts,
+            cliff_ts,
+            end_ts,
+            revocable,
+            bump: ctx.bumps.vesting,
+        });
+
+        // Emit event
+        emit!(VestingCreated {
+            mint: ctx.accounts.mint.key(),
+            vesting: ctx.accounts.vesting.key(),
+            beneficiary,
+            total_amount,
+            start_ts,
+            cliff_ts,
+            end_ts,
+            revocable,
+        });
+
+        msg!("Vesting {} tokens to {} until {}", total_amount, beneficiary, end_ts);
+
+        Ok(())
+    }
+
+    /// Pay the vested but unreleased tokens to the beneficiary
+    pub fn release(ctx: Context<ReleaseVesting>) -> Result<()> {
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.beneficiary_denylist_entry.as_ref())?;
+        require_attested(&ctx.accounts.mint_state, ctx.accounts.beneficiary_attestation.as_ref(), now)?;
+        let vesting = &mut ctx.accounts.vesting;
+        let amount = vesting.release(now)?;
+
+        // Create the ATA if it does not exist yet
+        let new_account = create_associated_account(
+            &ctx.accounts.beneficiary_token_account,
+            &ctx.accounts.beneficiary,
+            &ctx.accounts.mint,
+            &ctx.accounts.beneficiary,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+        )?;
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            thaw_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.beneficiary_token_account,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        // Transfer from the vault, signed by the vesting PDA
+        let cpi_accounts = SplTransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.beneficiary_token_account.to_account_info(),
+            authority: vesting.to_account_info(),
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let beneficiary = ctx.accounts.beneficiary.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[VESTING_SEED, mint_key.as_ref(), beneficiary.as_ref(), &[vesting.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        // Emit event
+        emit!(VestedTokensReleased {
+            mint: mint_key,
+            vesting: vesting.key(),
+            beneficiary,
+            amount,
+            total_released: vesting.released,
+        });
+
+        // New accounts of a default-frozen mint stay frozen once delivered to
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            freeze_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.beneficiary_token_account,
+                ctx.accounts.beneficiary_freeze_record.as_deref(),
+                &ctx.accounts.beneficiary,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        msg!("Released {} vested tokens to {}", amount, beneficiary);
+
+        Ok(())
+    }
+
+    /// Stop a revocable vesting schedule, returning the unvested tokens to the
+    /// authority. Tokens vested so far stay releasable by the beneficiary, and
+    /// the destination owner must be attested while the mint requires an allowlist.
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require_attested(&ctx.accounts.mint_state, ctx.accounts.destination_attestation.as_ref(), now)?;
+        let vesting = &mut ctx.accounts.vesting;
+        let unvested_amount = vesting.revoke(now)?;
+
+        if unvested_amount > 0 {
+            let cpi_accounts = SplTransferChecked {
+                from: ctx.accounts.vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: vesting.to_account_info(),
+            };
+
+            let mint_key = ctx.accounts.mint.key();
+            let beneficiary = vesting.beneficiary;
+            let signer_seeds: &[&[&[u8]]] = &[&[VESTING_SEED, mint_key.as_ref(), beneficiary.as_ref(), &[vesting.bump]]];
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            token_interface::transfer_checked(cpi_ctx, unvested_amount, ctx.accounts.mint.decimals)?;
+        }
+
+        // Emit event
+        emit!(VestingRevoked {
+            mint: ctx.accounts.mint.key(),
+            vesting: vesting.key(),
+            beneficiary: vesting.beneficiary,
+            unvested_amount,
+        });
+
+        msg!("Vesting revoked, {} unvested tokens returned", unvested_amount);
+
+        Ok(())
+    }
+
+    /// Open a public sale for a mint whose mint authority is held by the mint
+    /// state PDA. Buyers pay SOL into the treasury PDA along `curve`.
+    pub fn create_sale(
+        ctx: Context<CreateSale>,
+        curve: PriceCurve,
+        start_ts: i64,
+        end_ts: i64,
+        wallet_cap: Option<u64>,
+        sell_back: bool,
+    ) -> Result<()> {
+        // Verify mint authority; the program must be able to mint for buyers
+        let authority = ctx.accounts.authority.key();
+        let program_authority = validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &authority)?;
+        require!(program_authority, TokenError::InvalidSaleConfig);
+
+        curve.validate()?;
+        require!(start_ts < end_ts, TokenError::InvalidSaleConfig);
+        require!(!sell_back || curve.is_bonding(), TokenError::InvalidSaleConfig);
+        let wallet_cap = wallet_cap.unwrap_or(u64::MAX);
+        require!(wallet_cap > 0, TokenError::InvalidSaleConfig);
+
+        // Keep the treasury rent exempt so sell-backs can always draw it down
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let top_up = rent_exempt.saturating_sub(ctx.accounts.treasury.lamports());
+        if top_up > 0 {
+            let cpi_accounts = SystemTransfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+
+            system_program::transfer(cpi_ctx, top_up)?;
+        }
+
+        ctx.accounts.sale.set_inner(Sale {
+            mint: ctx.accounts.mint.key(),
+            authority,
+            curve,
+            start_ts,
+            end_ts,
+            wallet_cap,
+            sold: 0,
+            sell_back,
+            treasury_bump: ctx.bumps.treasury,
+            bump: ctx.bumps.sale,
+        });
+
+        // Emit event
+        emit!(SaleCreated {
+            mint: ctx.accounts.mint.key(),
+            sale: ctx.accounts.sale.key(),
+            curve,
+            start_ts,
+            end_ts,
+            wallet_cap,
+            sell_back,
+        });
+
+        msg!("Sale created: {:?} from {} to {}", curve, start_ts, end_ts);
+
+        Ok(())
+    }
+
+    /// Buy `amount` base units from the sale, paying at most `max_cost` lamports
+    pub fn buy_tokens(ctx: Context<BuyTokens>, amount: u64, max_cost: u64) -> Result<()> {
+        require!(amount > 0, TokenError::InvalidAmount);
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.buyer_denylist_entry.as_ref())?;
+        require_attested(&ctx.accounts.mint_state, ctx.accounts.buyer_attestation.as_ref(), now)?;
+        let sale = &ctx.accounts.sale;
+        sale.require_active(now)?;
+
+        // Price the purchase along the curve
+        let cost = calculate_mint_cost(&sale.curve, sale.sold, amount, ctx.accounts.mint.decimals)?;
+        require!(cost > 0, TokenError::InvalidAmount);
+        require!(cost <= max_cost, TokenError::SlippageExceeded);
+
+        // Check the wallet cap
+        let purchase = &mut ctx.accounts.purchase;
+        if purchase.buyer == Pubkey::default() {
+            purchase.sale = sale.key();
+            purchase.buyer = ctx.accounts.buyer.key();
+            purchase.bump = ctx.bumps.purchase;
+        }
+        purchase.record(amount, sale.wallet_cap)?;
+
+        // Check supply cap; purchases share a single rate-limit slot keyed by the sale
+        ctx.accounts.mint_state.check_supply_cap(ctx.accounts.mint.supply, amount)?;
+        enforce_rate_limit(&ctx.accounts.mint_state, ctx.accounts.rate_limit.as_mut(), &sale.key(), amount)?;
+
+        // Take payment
+        let cpi_accounts = SystemTransfer {
+            from: ctx.accounts.buyer.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+
+        system_program::transfer(cpi_ctx, cost)?;
+
+        // Create the ATA if it does not exist yet
+        let new_account = create_associated_account(
+            &ctx.accounts.buyer_token_account,
+            &ctx.accounts.buyer,
+            &ctx.accounts.mint,
+            &ctx.accounts.buyer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+        )?;
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            thaw_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.buyer_token_account,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        // Mint to the buyer, signed by the mint state PDA
+        let cpi_accounts = SplMintToChecked {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: ctx.accounts.mint_state.to_account_info(),
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::mint_to_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        ctx.accounts.mint_state.record_mint(amount)?;
+
+        let sale = &mut ctx.accounts.sale;
+        sale.sold = sale.sold.checked_add(amount).ok_or(TokenError::SupplyOverflow)?;
+
+        // Emit event
+        emit!(TokensPurchased {
+            mint: mint_key,
+            buyer: ctx.accounts.buyer.key(),
+            amount,
+            cost,
+        });
+
+        // New accounts of a default-frozen mint stay frozen once delivered to
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            freeze_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.buyer_token_account,
+                ctx.accounts.buyer_freeze_record.as_deref(),
+                &ctx.accounts.buyer,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        msg!("Sold {} tokens for {} lamports", amount, cost);
+
+        Ok(())
+    }
+
+    /// Sell `amount` base units back along a bonding curve for at least
+    /// `min_proceeds` lamports. The tokens are burned.
+    pub fn sell_tokens(ctx: Context<SellTokens>, amount: u64, min_proceeds: u64) -> Result<()> {
+        require!(amount > 0, TokenError::InvalidAmount);
+        ctx.accounts.mint_state.require_not_paused()?;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.seller_denylist_entry.as_ref())?;
+
+        let sale = &ctx.accounts.sale;
+        require!(sale.sell_back, TokenError::SellBackDisabled);
+        sale.require_active(Clock::get()?.unix_timestamp)?;
+        require!(amount <= sale.sold, TokenError::InsufficientFunds);
+
+        // Verify the seller's account
         require!(
-            ctx.accounts.mint.freeze_authority.is_some(),
-            TokenError::Unauthorized
+            ctx.accounts.seller_token_account.owner == ctx.accounts.seller.key(),
+            TokenError::InvalidOwner
         );
         require!(
-            ctx.accounts.mint.freeze_authority.unwrap() == ctx.accounts.freeze_authority.key(),
-            TokenError::Unauthorized
+            ctx.accounts.seller_token_account.mint == ctx.accounts.mint.key(),
+            TokenError::MintMismatch
         );
 
-        // Verify token account belongs to this mint
-        require!(
-            ctx.accounts.token_account.mint == ctx.accounts.mint.key(),
-            TokenError::MintMismatch
-        );
+        let remaining = sale.sold - amount;
+        let decimals = ctx.accounts.mint.decimals;
+        let proceeds = calculate_mint_cost(&sale.curve, remaining, amount, decimals)?;
+        require!(proceeds >= min_proceeds, TokenError::SlippageExceeded);
+
+        // Burn the tokens being sold
+        let cpi_accounts = SplBurnChecked {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.seller_token_account.to_account_info(),
+            authority: ctx.accounts.seller.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+
+        token_interface::burn_checked(cpi_ctx, amount, decimals)?;
+
+        // Pay out of the treasury, signed by the treasury PDA
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[TREASURY_SEED, mint_key.as_ref(), &[sale.treasury_bump]]];
+        let cpi_accounts = SystemTransfer {
+            from: ctx.accounts.treasury.to_account_info(),
+            to: ctx.accounts.seller.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(), cpi_accounts, signer_seeds);
+
+        system_program::transfer(cpi_ctx, proceeds)?;
+
+        ctx.accounts.sale.sold = remaining;
+
+        // Emit event
+        emit!(TokensSoldBack {
+            mint: mint_key,
+            seller: ctx.accounts.seller.key(),
+            amount,
+            proceeds,
+        });
+
+        msg!("Bought back {} tokens for {} lamports", amount, proceeds);
+
+        Ok(())
+    }
+
+    /// Withdraw sale proceeds to the sale authority, leaving the treasury rent
+    /// exempt and, until the sale ends, able to cover every outstanding sell-back
+    pub fn withdraw_sale_proceeds(ctx: Context<WithdrawSaleProceeds>, amount: u64) -> Result<()> {
+        require!(amount > 0, TokenError::InvalidAmount);
+
+        let sale = &ctx.accounts.sale;
+        let locked = Rent::get()?
+            .minimum_balance(0)
+            .checked_add(sale.sell_back_reserve(ctx.accounts.mint.decimals, Clock::get()?.unix_timestamp)?)
+            .ok_or(TokenError::PriceOverflow)?;
+        let available = ctx.accounts.treasury.lamports().saturating_sub(locked);
+        require!(amount <= available, TokenError::InsufficientFunds);
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[TREASURY_SEED, mint_key.as_ref(), &[sale.treasury_bump]]];
+        let cpi_accounts = SystemTransfer {
+            from: ctx.accounts.treasury.to_account_info(),
+            to: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(), cpi_accounts, signer_seeds);
+
+        system_program::transfer(cpi_ctx, amount)?;
+
+        // Emit event
+        emit!(SaleProceedsWithdrawn {
+            mint: mint_key,
+            authority: ctx.accounts.authority.key(),
+            amount,
+        });
+
+        msg!("Withdrew {} lamports of sale proceeds", amount);
+
+        Ok(())
+    }
+
+    /// Open an allowlisted presale for a mint whose mint authority is held by the
+    /// mint state PDA. `price` is in lamports per base unit.
+    pub fn create_presale(
+        ctx: Context<CreatePresale>,
+        merkle_root: [u8; 32],
+        price: u64,
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<()> {
+        // Verify mint authority; the program must be able to mint for buyers
+        let authority = ctx.accounts.authority.key();
+        let program_authority = validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &authority)?;
+        require!(program_authority, TokenError::InvalidSaleConfig);
+
+        require!(price > 0, TokenError::InvalidSaleConfig);
+        require!(start_ts < end_ts, TokenError::InvalidSaleConfig);
+
+        // Keep the treasury rent exempt
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let top_up = rent_exempt.saturating_sub(ctx.accounts.treasury.lamports());
+        if top_up > 0 {
+            let cpi_accounts = SystemTransfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+
+            system_program::transfer(cpi_ctx, top_up)?;
+        }
+
+        ctx.accounts.presale.set_inner(Presale {
+            mint: ctx.accounts.mint.key(),
+            authority,
+            merkle_root,
+            price,
+            start_ts,
+            end_ts,
+            total_sold: 0,
+            treasury_bump: ctx.bumps.treasury,
+            bump: ctx.bumps.presale,
+        });
+
+        // Emit event
+        emit!(PresaleCreated {
+            mint: ctx.accounts.mint.key(),
+            presale: ctx.accounts.presale.key(),
+            merkle_root,
+            price,
+            start_ts,
+            end_ts,
+        });
+
+        msg!("Presale created at {} lamports per unit from {} to {}", price, start_ts, end_ts);
+
+        Ok(())
+    }
+
+    /// Buy `amount` base units in the presale. The proof shows the buyer's
+    /// (wallet, max_amount) leaf is in the allowlist.
+    pub fn purchase_presale(
+        ctx: Context<PurchasePresale>,
+        amount: u64,
+        max_amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(amount > 0, TokenError::InvalidAmount);
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.buyer_denylist_entry.as_ref())?;
+        require_attested(&ctx.accounts.mint_state, ctx.accounts.buyer_attestation.as_ref(), now)?;
+        let presale = &ctx.accounts.presale;
+        presale.require_active(now)?;
+        presale.verify_allowance(&ctx.accounts.buyer.key(), max_amount, &proof)?;
+        let cost = presale.cost(amount)?;
+
+        // Track the wallet's purchases against its allowance
+        let purchase = &mut ctx.accounts.purchase;
+        if purchase.buyer == Pubkey::default() {
+            purchase.sale = presale.key();
+            purchase.buyer = ctx.accounts.buyer.key();
+            purchase.bump = ctx.bumps.purchase;
+        }
+        purchase.record(amount, max_amount)?;
+        let total_purchased = purchase.purchased;
+
+        // Check supply cap; purchases share a single rate-limit slot keyed by the presale
+        ctx.accounts.mint_state.check_supply_cap(ctx.accounts.mint.supply, amount)?;
+        enforce_rate_limit(&ctx.accounts.mint_state, ctx.accounts.rate_limit.as_mut(), &presale.key(), amount)?;
+
+        // Take payment
+        let cpi_accounts = SystemTransfer {
+            from: ctx.accounts.buyer.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+
+        system_program::transfer(cpi_ctx, cost)?;
+
+        // Create the ATA if it does not exist yet
+        let new_account = create_associated_account(
+            &ctx.accounts.buyer_token_account,
+            &ctx.accounts.buyer,
+            &ctx.accounts.mint,
+            &ctx.accounts.buyer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+        )?;
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            thaw_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.buyer_token_account,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        // Mint to the buyer, signed by the mint state PDA
+        let cpi_accounts = SplMintToChecked {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.buyer_token_account.to_account_info(),
+            authority: ctx.accounts.mint_state.to_account_info(),
+        };
+
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        token_interface::mint_to_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        ctx.accounts.mint_state.record_mint(amount)?;
+
+        let presale = &mut ctx.accounts.presale;
+        presale.total_sold = presale.total_sold.checked_add(amount).ok_or(TokenError::SupplyOverflow)?;
+
+        // Emit event
+        emit!(PresalePurchased {
+            mint: mint_key,
+            buyer: ctx.accounts.buyer.key(),
+            amount,
+            cost,
+            total_purchased,
+        });
+
+        // New accounts of a default-frozen mint stay frozen once delivered to
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            freeze_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.buyer_token_account,
+                ctx.accounts.buyer_freeze_record.as_deref(),
+                &ctx.accounts.buyer,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        msg!("Presale sold {} tokens for {} lamports", amount, cost);
+
+        Ok(())
+    }
+
+    /// Withdraw presale proceeds to the presale authority
+    pub fn withdraw_presale_proceeds(ctx: Context<WithdrawPresaleProceeds>, amount: u64) -> Result<()> {
+        require!(amount > 0, TokenError::InvalidAmount);
 
-        // Thaw the account
-        let cpi_accounts = SplThawAccount {
-            account: ctx.accounts.token_account.to_account_info(),
-            mint: ctx.accounts.mint.to_account_info(),
-            authority: ctx.accounts.freeze_authority.to_account_info(),
+        let available = ctx
+            .accounts
+            .treasury
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(amount <= available, TokenError::InsufficientFunds);
+
+        let presale_key = ctx.accounts.presale.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[TREASURY_SEED, presale_key.as_ref(), &[ctx.accounts.presale.treasury_bump]]];
+        let cpi_accounts = SystemTransfer {
+            from: ctx.accounts.treasury.to_account_info(),
+            to: ctx.accounts.authority.to_account_info(),
         };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(), cpi_accounts, signer_seeds);
 
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-
-        token::thaw_account(cpi_ctx)?;
+        system_program::transfer(cpi_ctx, amount)?;
 
         // Emit event
-        emit!(AccountThawed {
+        emit!(PresaleProceedsWithdrawn {
             mint: ctx.accounts.mint.key(),
-            account: ctx.accounts.token_account.key(),
+            authority: ctx.accounts.authority.key(),
+            amount,
         });
 
-        msg!("Thawed token account: {}", ctx.accounts.token_account.key());
+        msg!("Withdrew {} lamports of presale proceeds", amount);
 
         Ok(())
     }
 
-    /// Set freeze authority
-    pub fn set_freeze_authority(
-        ctx: Context<SetMintAuthority>,
-        new_authority: Option<Pubkey>,
+    /// Configure, enable or disable the faucet for a mint whose mint authority
+    /// is held by the mint state PDA
+    pub fn configure_faucet(
+        ctx: Context<ConfigureFaucet>,
+        drip_amount: u64,
+        cooldown_seconds: i64,
+        enabled: bool,
     ) -> Result<()> {
-        // Verify current freeze authority
-        require!(
-            ctx.accounts.mint.freeze_authority.is_some(),
-            TokenError::Unauthorized
-        );
-        require!(
-            ctx.accounts.mint.freeze_authority.unwrap() == ctx.accounts.current_authority.key(),
-            TokenError::Unauthorized
-        );
+        // Verify mint authority; drips are signed by the mint state PDA
+        let program_authority = validate_minter(&ctx.accounts.mint, &ctx.accounts.mint_state, &ctx.accounts.mint_authority.key())?;
+        require!(program_authority, TokenError::InvalidFaucetConfig);
+        require!(drip_amount > 0 && cooldown_seconds >= 0, TokenError::InvalidFaucetConfig);
 
-        // Set new freeze authority
-        let cpi_accounts = SplSetAuthority {
-            account_or_mint: ctx.accounts.mint.to_account_info(),
-            current_authority: ctx.accounts.current_authority.to_account_info(),
+        ctx.accounts.faucet.set_inner(Faucet {
+            mint: ctx.accounts.mint.key(),
+            drip_amount,
+            cooldown_seconds,
+            enabled,
+            bump: ctx.bumps.faucet,
+        });
+
+        // Emit event
+        emit!(FaucetConfigured {
+            mint: ctx.accounts.mint.key(),
+            drip_amount,
+            cooldown_seconds,
+            enabled,
+        });
+
+        msg!("Faucet {}: {} tokens every {}s", if enabled { "enabled" } else { "disabled" }, drip_amount, cooldown_seconds);
+
+        Ok(())
+    }
+
+    /// Mint the faucet's drip amount to the calling wallet, at most once per cooldown
+    pub fn drip_faucet(ctx: Context<DripFaucet>) -> Result<()> {
+        ctx.accounts.mint_state.require_not_paused()?;
+
+        let faucet = &ctx.accounts.faucet;
+        let amount = faucet.drip_amount;
+        let claim = &mut ctx.accounts.faucet_claim;
+        if claim.wallet == Pubkey::default() {
+            claim.mint = ctx.accounts.mint.key();
+            claim.wallet = ctx.accounts.wallet.key();
+            claim.bump = ctx.bumps.faucet_claim;
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let next_drip_at = faucet.drip(claim, now)?;
+        require_not_denylisted(&ctx.accounts.mint_state, ctx.accounts.wallet_denylist_entry.as_ref())?;
+        require_attested(&ctx.accounts.mint_state, ctx.accounts.wallet_attestation.as_ref(), now)?;
+
+        // Check supply cap; drips share a single rate-limit slot keyed by the faucet
+        ctx.accounts.mint_state.check_supply_cap(ctx.accounts.mint.supply, amount)?;
+        enforce_rate_limit(&ctx.accounts.mint_state, ctx.accounts.rate_limit.as_mut(), &faucet.key(), amount)?;
+
+        // Create the ATA if it does not exist yet
+        let new_account = create_associated_account(
+            &ctx.accounts.wallet_token_account,
+            &ctx.accounts.wallet,
+            &ctx.accounts.mint,
+            &ctx.accounts.wallet,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+        )?;
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            thaw_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.wallet_token_account,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        // Mint to the wallet, signed by the mint state PDA
+        let cpi_accounts = SplMintToChecked {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.wallet_token_account.to_account_info(),
+            authority: ctx.accounts.mint_state.to_account_info(),
         };
 
+        let mint_key = ctx.accounts.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
         let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
-        token::set_authority(cpi_ctx, AuthorityType::FreezeAccount, new_authority)?;
+        token_interface::mint_to_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        ctx.accounts.mint_state.record_mint(amount)?;
 
-        match new_authority {
-            Some(authority) => {
-                msg!("Freeze authority changed to: {}", authority);
-            }
-            None => {
-                msg!("Freeze authority revoked permanently");
-            }
+        // Emit event
+        emit!(FaucetDripped {
+            mint: mint_key,
+            wallet: ctx.accounts.wallet.key(),
+            amount,
+            next_drip_at,
+        });
+
+        // New accounts of a default-frozen mint stay frozen once delivered to
+        if new_account && needs_default_freeze(&ctx.accounts.mint_state) {
+            freeze_new_account(
+                &ctx.accounts.mint,
+                &ctx.accounts.mint_state,
+                &ctx.accounts.wallet_token_account,
+                ctx.accounts.wallet_freeze_record.as_deref(),
+                &ctx.accounts.wallet,
+                &ctx.accounts.system_program,
+                &ctx.accounts.token_program,
+            )?;
         }
 
+        msg!("Faucet dripped {} tokens; next drip at {}", amount, next_drip_at);
+
         Ok(())
     }
 
@@ -816,7 +6952,7 @@ pub mod spl_token_mint {
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        token::close_account(cpi_ctx)?;
+        token_interface::close_account(cpi_ctx)?;
 
         msg!("Closed token account: {}", ctx.accounts.token_account.key());
 
@@ -824,107 +6960,822 @@ pub mod spl_token_mint {
     }
 }
 
-// Helper function to create metadata instruction data
-fn create_metadata_instruction_data(
-    _metadata_key: Pubkey,
-    _mint_key: Pubkey,
-    _mint_authority_key: Pubkey,
-    payer_key: Pubkey,
-    _update_authority_key: Pubkey,
-    name: String,
-    symbol: String,
-    uri: String,
-    seller_fee_basis_points: u16,
-) -> Vec<u8> {
-    // This is a simplified version - in production, you'd want to use proper Metaplex instruction encoding
-    // For now, this creates the basic structure needed for metadata creation
-    let mut data = Vec::new();
-    
-    // Instruction discriminator for CreateMetadataAccountV3 (33 in decimal)
-    data.push(33);
-    
-    // Add basic metadata fields (this is a simplified encoding)
-    // In production, use proper borsh serialization with Metaplex types
-    data.extend_from_slice(&name.len().to_le_bytes());
-    data.extend_from_slice(name.as_bytes());
-    data.extend_from_slice(&symbol.len().to_le_bytes());
-    data.extend_from_slice(symbol.as_bytes());
-    data.extend_from_slice(&uri.len().to_le_bytes());
-    data.extend_from_slice(uri.as_bytes());
-    data.extend_from_slice(&seller_fee_basis_points.to_le_bytes());
-    
-    // Add creator info (simplified)
-    data.push(1); // has creators
-    data.extend_from_slice(&payer_key.to_bytes());
-    data.push(1); // verified
-    data.push(100); // share
-    
-    // Collection and uses (none)
-    data.push(0); // no collection
-    data.push(0); // no uses
-    
-    data
-}
-
 // Utility functions for common operations
 impl MintDestination {
     pub fn new(recipient: Pubkey, amount: u64) -> Self {
         Self { recipient, amount }
     }
 
-    pub fn validate(&self) -> Result<()> {
-        require!(self.amount > 0, TokenError::InvalidAmount);
-        Ok(())
+    pub fn validate(&self) -> Result<()> {
+        require!(self.amount > 0, TokenError::InvalidAmount);
+        Ok(())
+    }
+}
+
+// Helper functions for PDA derivation
+pub fn get_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address(wallet, mint)
+}
+
+pub fn get_associated_token_address_with_program_id(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address_with_program_id(wallet, mint, token_program)
+}
+
+pub fn get_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "metadata".as_bytes(),
+            &mpl_token_metadata::ID.to_bytes(),
+            mint.as_ref(),
+        ],
+        &mpl_token_metadata::ID,
+    )
+}
+
+// Additional validation helpers
+// `authority` may be a signing key or the mint's multisig PDA (see `execute_proposal`)
+pub fn validate_mint_authority(mint: &InterfaceAccount<Mint>, authority: &Pubkey) -> Result<()> {
+    require!(
+        mint.mint_authority.is_some(),
+        TokenError::Unauthorized
+    );
+    require!(
+        mint.mint_authority.unwrap() == *authority,
+        TokenError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Verify `authority` may mint: either it is the mint authority, or the mint state
+/// PDA holds the mint authority and `authority` is its recorded operator.
+/// Returns true when CPIs must be signed by the mint state PDA.
+pub fn validate_minter(
+    mint: &InterfaceAccount<Mint>,
+    mint_state: &Account<TokenMintState>,
+    authority: &Pubkey,
+) -> Result<bool> {
+    if Option::<Pubkey>::from(mint.mint_authority) == Some(mint_state.key()) {
+        require!(
+            mint_state.mint_authority == *authority,
+            TokenError::Unauthorized
+        );
+        return Ok(true);
+    }
+
+    validate_mint_authority(mint, authority)?;
+    Ok(false)
+}
+
+/// Verify `signer` may update metadata held by `update_authority`
+pub fn validate_metadata_update(update_authority: &Pubkey, is_mutable: bool, signer: &Pubkey) -> Result<()> {
+    require!(update_authority == signer, TokenError::Unauthorized);
+    require!(is_mutable, TokenError::MetadataImmutable);
+    Ok(())
+}
+
+pub fn validate_freeze_authority(mint: &InterfaceAccount<Mint>, authority: &Pubkey) -> Result<()> {
+    require!(
+        mint.freeze_authority.is_some(),
+        TokenError::Unauthorized
+    );
+    require!(
+        mint.freeze_authority.unwrap() == *authority,
+        TokenError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Verify `authority` may move or revoke the freeze authority: it holds the freeze
+/// authority itself or, while the mint state PDA holds it, it is the mint's minter
+/// or an admin role holder. Returns true when CPIs must be signed by the mint state PDA.
+/// Default-frozen mints keep the freeze authority in the mint state PDA.
+pub fn authorize_freeze_authority_change(
+    mint: &InterfaceAccount<Mint>,
+    mint_state: &Account<TokenMintState>,
+    roles: Option<&Account<Roles>>,
+    authority: &Pubkey,
+) -> Result<bool> {
+    if Option::<Pubkey>::from(mint.freeze_authority) != Some(mint_state.key()) {
+        validate_freeze_authority(mint, authority)?;
+        return Ok(false);
+    }
+
+    if !roles.is_some_and(|roles| roles.has_role(authority, Role::Admin)) {
+        validate_minter(mint, mint_state, authority)?;
+    }
+
+    // Default-frozen mints freeze the accounts they create with the mint state PDA
+    require!(!mint_state.default_frozen, TokenError::FreezeAuthorityRequired);
+    Ok(true)
+}
+
+/// Changes that take effect without a handover: revocation, or moving the
+/// authority to one of the mint's own PDAs (mint state or multisig)
+pub fn is_immediate_authority_change(
+    new_authority: Option<Pubkey>,
+    mint_state: Pubkey,
+    multisig: Option<Pubkey>,
+) -> bool {
+    match new_authority {
+        None => true,
+        Some(authority) => authority == mint_state || multisig == Some(authority),
+    }
+}
+
+/// Hand an escrowed authority from the pending authority PDA to `new_authority`
+pub fn release_escrowed_authority<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    pending_info: &AccountInfo<'info>,
+    pending: &PendingAuthority,
+    token_program: &Interface<'info, TokenInterface>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    let cpi_accounts = SplSetAuthority {
+        account_or_mint: mint.to_account_info(),
+        current_authority: pending_info.clone(),
+    };
+
+    let mint_key = mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        PENDING_AUTHORITY_SEED,
+        mint_key.as_ref(),
+        pending.kind.seed(),
+        &[pending.bump],
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+
+    token_interface::set_authority(cpi_ctx, pending.kind.into(), Some(new_authority))
+}
+
+/// Verify `authority` may mint `amount`: as checked by `validate_minter`, or as a
+/// minter role holder with enough allowance while the mint state PDA holds the
+/// mint authority. Returns true when CPIs must be signed by the mint state PDA.
+pub fn authorize_minter(
+    mint: &InterfaceAccount<Mint>,
+    mint_state: &Account<TokenMintState>,
+    roles: Option<&mut Account<Roles>>,
+    authority: &Pubkey,
+    amount: u64,
+) -> Result<bool> {
+    let program_authority = Option::<Pubkey>::from(mint.mint_authority) == Some(mint_state.key());
+    if let Some(roles) = roles {
+        if program_authority && roles.has_role(authority, Role::Minter) {
+            roles.consume(authority, Role::Minter, amount)?;
+            return Ok(true);
+        }
+    }
+
+    validate_minter(mint, mint_state, authority)
+}
+
+/// Spend rate limit capacity for `minter` when the mint has a rate limit configured
+pub fn enforce_rate_limit(
+    mint_state: &TokenMintState,
+    rate_limit: Option<&mut Account<RateLimit>>,
+    minter: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    if !mint_state.rate_limited {
+        return Ok(());
+    }
+
+    let rate_limit = rate_limit.ok_or(TokenError::AccountNotInitialized)?;
+    rate_limit.consume(minter, amount, Clock::get()?.unix_timestamp)
+}
+
+/// Reject a list of accounts that names any account twice
+pub fn require_unique_accounts<'a>(keys: impl Iterator<Item = &'a Pubkey>) -> Result<()> {
+    let mut seen: Vec<&Pubkey> = Vec::new();
+    for key in keys {
+        require!(!seen.contains(&key), TokenError::DuplicateAccount);
+        seen.push(key);
+    }
+    Ok(())
+}
+
+/// Verify `authority` may move `amount` tokens out of `account`: it is the owner,
+/// or the delegate with at least `amount` still approved
+pub fn authorize_spender(account: &SplTokenAccount, authority: &Pubkey, amount: u64) -> Result<()> {
+    if account.owner == *authority {
+        return Ok(());
+    }
+
+    require!(
+        Option::<Pubkey>::from(account.delegate) == Some(*authority),
+        TokenError::InvalidOwner
+    );
+    require!(account.delegated_amount >= amount, TokenError::DelegateAllowanceExceeded);
+    Ok(())
+}
+
+/// Shared body of `batch_freeze_accounts` (`freeze` holds the reason and case ID)
+/// and `batch_thaw_accounts`. `remaining_accounts` holds `[token_account,
+/// freeze_record]` pairs, plus the record's rent payer when thawing; every account
+/// must belong to the mint, and accounts already in the target state are skipped.
+fn set_accounts_frozen<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchFreezeAccounts<'info>>,
+    freeze: Option<(FreezeReason, String)>,
+) -> Result<BatchFreezeResult> {
+    let freezing = freeze.is_some();
+    let per_entry = if freezing { BATCH_FREEZE_ACCOUNTS_PER_ENTRY } else { BATCH_THAW_ACCOUNTS_PER_ENTRY };
+    let count = ctx.remaining_accounts.len() / per_entry;
+    require!(
+        count * per_entry == ctx.remaining_accounts.len()
+            && count > 0
+            && count <= MAX_BATCH_FREEZE_SIZE,
+        TokenError::InvalidRemainingAccounts
+    );
+
+    // Verify every account belongs to this mint before changing any
+    let mint_key = ctx.accounts.mint.key();
+    require_unique_accounts(
+        ctx.remaining_accounts
+            .iter()
+            .step_by(per_entry)
+            .map(|info| info.key),
+    )?;
+    let mut pending = Vec::with_capacity(count);
+    let mut skipped = Vec::new();
+    for pair in ctx.remaining_accounts.chunks(per_entry) {
+        let token_account = InterfaceAccount::<TokenAccount>::try_from(&pair[0])?;
+        require!(token_account.mint == mint_key, TokenError::MintMismatch);
+        let (record_key, record_bump) =
+            Pubkey::find_program_address(&[FREEZE_RECORD_SEED, pair[0].key.as_ref()], &crate::ID);
+        require!(pair[1].key() == record_key, TokenError::InvalidFreezeRecord);
+
+        if token_account.is_frozen() == freezing {
+            skipped.push(pair[0].key());
+        } else {
+            pending.push((&pair[0], &pair[1], pair.get(2), record_bump));
+        }
+    }
+
+    // Verify freeze authority, or a freezer role when the mint state PDA holds it.
+    // Role allowances are only charged for the accounts that actually change.
+    let program_authority = authorize_freezer(
+        &ctx.accounts.mint,
+        &ctx.accounts.mint_state,
+        ctx.accounts.roles.as_mut(),
+        &ctx.accounts.freeze_authority.key(),
+        pending.len() as u64,
+    )?;
+
+    let authority = if program_authority {
+        ctx.accounts.mint_state.to_account_info()
+    } else {
+        ctx.accounts.freeze_authority.to_account_info()
+    };
+    let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[ctx.accounts.mint_state.bump]]];
+    let now = Clock::get()?.unix_timestamp;
+    for (account, record, record_payer, record_bump) in &pending {
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        if let Some((reason, case_id)) = &freeze {
+            let cpi_accounts = SplFreezeAccount {
+                account: (*account).clone(),
+                mint: ctx.accounts.mint.to_account_info(),
+                authority: authority.clone(),
+            };
+            token_interface::freeze_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+
+            write_freeze_record(
+                record,
+                &ctx.accounts.freeze_authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                *record_bump,
+                FreezeRecord {
+                    mint: mint_key,
+                    account: account.key(),
+                    reason: *reason,
+                    case_id: case_id.clone(),
+                    frozen_at: now,
+                    frozen_by: ctx.accounts.freeze_authority.key(),
+                    payer: ctx.accounts.freeze_authority.key(),
+                    bump: *record_bump,
+                },
+            )?;
+
+            // Emit event
+            emit!(AccountFrozen {
+                mint: mint_key,
+                account: account.key(),
+                reason: *reason,
+                case_id: case_id.clone(),
+            });
+        } else {
+            let cpi_accounts = SplThawAccount {
+                account: (*account).clone(),
+                mint: ctx.accounts.mint.to_account_info(),
+                authority: authority.clone(),
+            };
+            token_interface::thaw_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+
+            close_freeze_record(record, record_payer.cloned())?;
+
+            // Emit event
+            emit!(AccountThawed {
+                mint: mint_key,
+                account: account.key(),
+            });
+        }
+    }
+
+    for account in &skipped {
+        msg!("Skipped {}: already {}", account, if freezing { "frozen" } else { "thawed" });
+    }
+    msg!("{} {} token accounts, skipped {}", if freezing { "Froze" } else { "Thawed" }, pending.len(), skipped.len());
+
+    Ok(BatchFreezeResult {
+        updated: pending.len() as u32,
+        skipped,
+    })
+}
+
+/// Create the program-owned PDA `account` with `space` bytes, paid by `payer`.
+/// An address that already belongs to the program is left as is.
+fn create_program_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+) -> Result<()> {
+    if account.owner == &crate::ID {
+        return Ok(());
+    }
+
+    let signer_seeds: &[&[&[u8]]] = &[seeds];
+    let rent_exempt = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        let cpi_accounts = CreateAccount { from: payer.clone(), to: account.clone() };
+        let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds);
+        system_program::create_account(cpi_ctx, rent_exempt, space as u64, &crate::ID)?;
+    } else {
+        // Someone pre-funded the address; top it up and take ownership instead
+        let top_up = rent_exempt.saturating_sub(account.lamports());
+        if top_up > 0 {
+            let cpi_accounts = SystemTransfer { from: payer.clone(), to: account.clone() };
+            system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), top_up)?;
+        }
+        let cpi_accounts = Allocate { account_to_allocate: account.clone() };
+        system_program::allocate(
+            CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds),
+            space as u64,
+        )?;
+        let cpi_accounts = Assign { account_to_assign: account.clone() };
+        system_program::assign(
+            CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds),
+            &crate::ID,
+        )?;
+    }
+    Ok(())
+}
+
+/// Create (or overwrite a stale) freeze record PDA at `record`, paid by `payer`
+fn write_freeze_record<'info>(
+    record: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    bump: u8,
+    data: FreezeRecord,
+) -> Result<()> {
+    create_program_account(
+        record,
+        payer,
+        system_program,
+        &[FREEZE_RECORD_SEED, data.account.as_ref(), &[bump]],
+        FreezeRecord::LEN,
+    )?;
+
+    let mut buffer = record.try_borrow_mut_data()?;
+    data.try_serialize(&mut &mut buffer[..])
+}
+
+/// Verify the multisig holds the `kind` authority, returning whether it does so
+/// through the mint state PDA (program authority mode)
+pub fn validate_multisig_authority(
+    mint: &InterfaceAccount<Mint>,
+    mint_state: &Account<TokenMintState>,
+    multisig: &Pubkey,
+    kind: AuthorityKind,
+) -> Result<bool> {
+    match kind {
+        AuthorityKind::Mint => validate_minter(mint, mint_state, multisig),
+        AuthorityKind::Freeze => {
+            validate_freeze_authority(mint, multisig)?;
+            Ok(false)
+        }
+    }
+}
+
+/// Move an authority the multisig holds, signing with the multisig PDA or, in
+/// program authority mode, the mint state PDA
+fn set_multisig_held_authority(
+    accounts: &mut ExecuteProposal,
+    kind: AuthorityKind,
+    new_authority: Option<Pubkey>,
+    program_authority: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // Program authority mode only rotates the minting operator
+    if let (true, Some(operator)) = (program_authority, new_authority) {
+        accounts.mint_state.mint_authority = operator;
+        return Ok(());
+    }
+
+    let current_authority = if program_authority {
+        accounts.mint_state.to_account_info()
+    } else {
+        accounts.multisig.to_account_info()
+    };
+    let cpi_accounts = SplSetAuthority {
+        account_or_mint: accounts.mint.to_account_info(),
+        current_authority,
+    };
+    let cpi_program = accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+    token_interface::set_authority(cpi_ctx, kind.into(), new_authority)?;
+    if kind == AuthorityKind::Mint {
+        accounts.mint_state.mint_authority = new_authority.unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// The queued change PDA for `kind` passed to a multisig proposal, with its bump
+fn queued_change_account<'a, 'info>(
+    account: Option<&'a UncheckedAccount<'info>>,
+    mint: &Pubkey,
+    kind: AuthorityKind,
+) -> Result<(&'a AccountInfo<'info>, u8)> {
+    let account = account.ok_or(TokenError::InvalidProposalAccounts)?;
+    let (expected, bump) = Pubkey::find_program_address(&[QUEUED_CHANGE_SEED, mint.as_ref(), kind.seed()], &crate::ID);
+    require!(account.key() == expected, TokenError::InvalidProposalAccounts);
+    Ok((account, bump))
+}
+
+/// The pending authority PDA for `kind` passed to a multisig proposal, with its bump
+fn pending_authority_account<'a, 'info>(
+    account: Option<&'a UncheckedAccount<'info>>,
+    mint: &Pubkey,
+    kind: AuthorityKind,
+) -> Result<(&'a AccountInfo<'info>, u8)> {
+    let account = account.ok_or(TokenError::InvalidProposalAccounts)?;
+    let (expected, bump) = Pubkey::find_program_address(&[PENDING_AUTHORITY_SEED, mint.as_ref(), kind.seed()], &crate::ID);
+    require!(account.key() == expected, TokenError::InvalidProposalAccounts);
+    Ok((account, bump))
+}
+
+/// The freeze record PDA of `token_account` passed to a multisig proposal, with its bump
+fn freeze_record_account<'a, 'info>(
+    account: Option<&'a UncheckedAccount<'info>>,
+    token_account: &Pubkey,
+) -> Result<(&'a AccountInfo<'info>, u8)> {
+    let account = account.ok_or(TokenError::InvalidFreezeRecord)?;
+    let (expected, bump) = Pubkey::find_program_address(&[FREEZE_RECORD_SEED, token_account.as_ref()], &crate::ID);
+    require!(account.key() == expected, TokenError::InvalidFreezeRecord);
+    Ok((account, bump))
+}
+
+/// Load a queued authority change and check that `multisig` queued it
+fn load_multisig_queued_change(account: &AccountInfo, multisig: &Pubkey) -> Result<QueuedAuthorityChange> {
+    require!(is_initialized_pda(account), TokenError::InvalidProposalAccounts);
+    let queued = QueuedAuthorityChange::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    require!(queued.queued_by == *multisig, TokenError::Unauthorized);
+    Ok(queued)
+}
+
+/// Mint account size, with room for the DefaultAccountState extension on
+/// default-frozen Token-2022 mints
+pub fn mint_account_space(token_program: &Pubkey, default_frozen: bool) -> Result<usize> {
+    if default_frozen && *token_program == anchor_spl::token_2022::ID {
+        Ok(ExtensionType::try_calculate_account_len::<SplMint>(&[ExtensionType::DefaultAccountState])?)
+    } else {
+        Ok(SplMint::LEN)
+    }
+}
+
+/// Create `owner`'s associated token account at `account` if it does not exist
+/// yet, returning whether it was just created
+fn create_associated_account<'info>(
+    account: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+) -> Result<bool> {
+    let new_account = account.data_is_empty();
+    let cpi_accounts = anchor_spl::associated_token::Create {
+        payer: payer.clone(),
+        associated_token: account.clone(),
+        authority: owner.clone(),
+        mint: mint.to_account_info(),
+        system_program: system_program.clone(),
+        token_program: token_program.clone(),
+    };
+    let cpi_ctx = CpiContext::new(associated_token_program.clone(), cpi_accounts);
+
+    anchor_spl::associated_token::create_idempotent(cpi_ctx)?;
+    Ok(new_account)
+}
+
+/// Default-frozen mints deliver to the accounts they create and then keep them
+/// frozen, whichever token program they use
+pub fn needs_default_freeze(mint_state: &TokenMintState) -> bool {
+    mint_state.default_frozen
+}
+
+/// Token-2022 creates the accounts of a default-frozen mint frozen, so a new
+/// account is thawed with the mint state PDA before its first delivery
+fn thaw_new_account<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    mint_state: &Account<'info, TokenMintState>,
+    account: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if token_program.key() != anchor_spl::token_2022::ID {
+        return Ok(());
+    }
+
+    require!(
+        Option::<Pubkey>::from(mint.freeze_authority) == Some(mint_state.key()),
+        TokenError::FreezeAuthorityRequired
+    );
+    let cpi_accounts = SplThawAccount {
+        account: account.clone(),
+        mint: mint.to_account_info(),
+        authority: mint_state.to_account_info(),
+    };
+
+    let mint_key = mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[mint_state.bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+
+    token_interface::thaw_account(cpi_ctx)
+}
+
+/// Freeze a newly created token account of a default-frozen mint, signing with
+/// the mint state PDA, and open its freeze record at `record` paid by `signer`
+fn freeze_new_account<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    mint_state: &Account<'info, TokenMintState>,
+    account: &AccountInfo<'info>,
+    record: Option<&AccountInfo<'info>>,
+    signer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let record = record.ok_or(TokenError::InvalidFreezeRecord)?;
+    let (record_key, record_bump) =
+        Pubkey::find_program_address(&[FREEZE_RECORD_SEED, account.key.as_ref()], &crate::ID);
+    require!(record.key() == record_key, TokenError::InvalidFreezeRecord);
+
+    require!(
+        Option::<Pubkey>::from(mint.freeze_authority) == Some(mint_state.key()),
+        TokenError::FreezeAuthorityRequired
+    );
+    let frozen_by = mint_state.key();
+    let cpi_accounts = SplFreezeAccount {
+        account: account.clone(),
+        mint: mint.to_account_info(),
+        authority: mint_state.to_account_info(),
+    };
+
+    let mint_key = mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[MINT_STATE_SEED, mint_key.as_ref(), &[mint_state.bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+
+    token_interface::freeze_account(cpi_ctx)?;
+
+    write_freeze_record(
+        record,
+        signer,
+        system_program,
+        record_bump,
+        FreezeRecord {
+            mint: mint_key,
+            account: account.key(),
+            reason: FreezeReason::DefaultState,
+            case_id: String::new(),
+            frozen_at: Clock::get()?.unix_timestamp,
+            frozen_by,
+            payer: signer.key(),
+            bump: record_bump,
+        },
+    )?;
+
+    // Emit event
+    emit!(AccountFrozen {
+        mint: mint_key,
+        account: account.key(),
+        reason: FreezeReason::DefaultState,
+        case_id: String::new(),
+    });
+    Ok(())
+}
+
+/// Close the freeze record at `record` if one exists, refunding its rent to the
+/// payer stored in it
+fn close_freeze_record<'info>(record: &AccountInfo<'info>, payer: Option<AccountInfo<'info>>) -> Result<()> {
+    if record.owner != &crate::ID {
+        return Ok(());
+    }
+
+    let stored_payer = FreezeRecord::try_deserialize(&mut &record.try_borrow_data()?[..])?.payer;
+    let payer = payer.ok_or(TokenError::InvalidRecordPayer)?;
+    require!(payer.key() == stored_payer, TokenError::InvalidRecordPayer);
+    close_program_account(record, &payer)
+}
+
+/// Close the program account at `account` (a freeze record, queued authority
+/// change or pending handover) if one exists, refunding its rent to `destination`
+fn close_program_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    if account.owner != &crate::ID {
+        return Ok(());
+    }
+
+    let refund = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(refund)
+        .ok_or(TokenError::SupplyOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.assign(&system_program::ID);
+    account.resize(0)?;
+    Ok(())
+}
+
+/// Reject a wallet whose denylist PDA `entry` exists. Does nothing until a
+/// compliance authority is set; from then on the entry account must be passed.
+pub fn require_not_denylisted(mint_state: &TokenMintState, entry: Option<&UncheckedAccount>) -> Result<()> {
+    if mint_state.compliance_authority.is_none() {
+        return Ok(());
+    }
+
+    let entry = entry.ok_or(TokenError::AccountNotInitialized)?;
+    require!(!is_initialized_pda(entry), TokenError::Denylisted);
+    Ok(())
+}
+
+/// `require_not_denylisted` for an entry passed through `remaining_accounts`
+pub fn require_not_denylisted_wallet(mint: &Pubkey, wallet: &Pubkey, entry: &AccountInfo) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[DENYLIST_SEED, mint.as_ref(), wallet.as_ref()], &crate::ID);
+    require!(entry.key() == expected, TokenError::InvalidRemainingAccounts);
+    require!(!is_initialized_pda(entry), TokenError::Denylisted);
+    Ok(())
+}
+
+/// Reject a recipient without a valid KYC attestation PDA `attestation` while
+/// the mint requires an allowlist
+pub fn require_attested(mint_state: &TokenMintState, attestation: Option<&UncheckedAccount>, now: i64) -> Result<()> {
+    if !mint_state.kyc_required {
+        return Ok(());
+    }
+
+    check_attestation(attestation.ok_or(TokenError::AttestationMissing)?, now)
+}
+
+/// `require_attested` for an attestation passed without seeds verification
+pub fn require_attested_wallet(mint: &Pubkey, wallet: &Pubkey, attestation: &AccountInfo, now: i64) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[ATTESTATION_SEED, mint.as_ref(), wallet.as_ref()], &crate::ID);
+    require!(attestation.key() == expected, TokenError::AttestationMissing);
+    check_attestation(attestation, now)
+}
+
+fn check_attestation(info: &AccountInfo, now: i64) -> Result<()> {
+    require!(is_initialized_pda(info), TokenError::AttestationMissing);
+    let attestation = Attestation::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require!(attestation.is_valid(now), TokenError::AttestationExpired);
+    Ok(())
+}
+
+/// Batch instructions take a denylist PDA per recipient once a compliance authority
+/// is set, then a KYC attestation PDA while an allowlist is required, then the
+/// destination's freeze record PDA when new accounts get `freeze_records`
+pub fn batch_accounts_per_destination(mint_state: &TokenMintState, freeze_records: bool) -> usize {
+    BATCH_MINT_ACCOUNTS_PER_DESTINATION
+        + mint_state.compliance_authority.is_some() as usize
+        + mint_state.kyc_required as usize
+        + freeze_records as usize
+}
+
+/// Check a batch recipient against the denylist and KYC allowlist using the PDAs
+/// that follow its `[destination, recipient]` pair
+pub fn check_batch_recipient(
+    mint_state: &TokenMintState,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    extra_accounts: &[AccountInfo],
+    now: i64,
+) -> Result<()> {
+    let mut extra_accounts = extra_accounts.iter();
+    if mint_state.compliance_authority.is_some() {
+        let entry = extra_accounts.next().ok_or(TokenError::InvalidRemainingAccounts)?;
+        require_not_denylisted_wallet(mint, recipient, entry)?;
+    }
+    if mint_state.kyc_required {
+        let attestation = extra_accounts.next().ok_or(TokenError::InvalidRemainingAccounts)?;
+        require_attested_wallet(mint, recipient, attestation, now)?;
+    }
+    Ok(())
+}
+
+/// True when a seeds-verified PDA address holds one of this program's accounts
+fn is_initialized_pda(info: &AccountInfo) -> bool {
+    info.owner == &crate::ID && !info.data_is_empty()
+}
+
+/// Verify `authority` may freeze or thaw accounts: it is the freeze authority, or
+/// the mint state PDA is and `authority` holds the freezer role.
+/// Returns true when CPIs must be signed by the mint state PDA.
+/// A freezer role's allowance counts accounts, so batches consume `accounts`.
+pub fn authorize_freezer(
+    mint: &InterfaceAccount<Mint>,
+    mint_state: &Account<TokenMintState>,
+    roles: Option<&mut Account<Roles>>,
+    authority: &Pubkey,
+    accounts: u64,
+) -> Result<bool> {
+    if Option::<Pubkey>::from(mint.freeze_authority) == Some(mint_state.key()) {
+        let roles = roles.ok_or(TokenError::MissingRole)?;
+        roles.consume(authority, Role::Freezer, accounts)?;
+        return Ok(true);
     }
+
+    validate_freeze_authority(mint, authority)?;
+    Ok(false)
 }
 
-// Helper functions for PDA derivation
-pub fn get_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
-    anchor_spl::associated_token::get_associated_token_address(wallet, mint)
+/// Merkle leaf for an airdrop allocation; the prefix separates leaves from nodes
+pub fn airdrop_leaf(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[&[0], &index.to_le_bytes(), claimant.as_ref(), &amount.to_le_bytes()]).to_bytes()
 }
 
-pub fn get_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(
-        &[
-            "metadata".as_bytes(),
-            &mpl_token_metadata::ID.to_bytes(),
-            mint.as_ref(),
-        ],
-        &mpl_token_metadata::ID,
-    )
+/// Presale allowlist leaf; shorter than an airdrop leaf, so the two never collide
+pub fn allowlist_leaf(wallet: &Pubkey, max_amount: u64) -> [u8; 32] {
+    hashv(&[&[0], wallet.as_ref(), &max_amount.to_le_bytes()]).to_bytes()
 }
 
-// Additional validation helpers
-pub fn validate_mint_authority(mint: &Account<Mint>, authority: &Pubkey) -> Result<()> {
-    require!(
-        mint.mint_authority.is_some(),
-        TokenError::Unauthorized
-    );
-    require!(
-        mint.mint_authority.unwrap() == *authority,
-        TokenError::Unauthorized
-    );
-    Ok(())
+/// Verify a merkle proof built with sorted pairs, so no position bits are needed
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[&[1], &left, &right]).to_bytes()
+    });
+    computed == *root
 }
 
-pub fn validate_freeze_authority(mint: &Account<Mint>, authority: &Pubkey) -> Result<()> {
-    require!(
-        mint.freeze_authority.is_some(),
-        TokenError::Unauthorized
-    );
-    require!(
-        mint.freeze_authority.unwrap() == *authority,
-        TokenError::Unauthorized
-    );
+// Token program restrictions (see the `spl-token-only` and `token-2022-only` features)
+#[cfg(all(feature = "spl-token-only", feature = "token-2022-only"))]
+compile_error!("features `spl-token-only` and `token-2022-only` are mutually exclusive");
+
+/// Token-2022 mints must use the `*_checked` instruction variants
+pub fn require_legacy_token_program(program_id: &Pubkey) -> Result<()> {
+    require!(*program_id != anchor_spl::token_2022::ID, TokenError::CheckedInstructionRequired);
     Ok(())
 }
 
+pub fn is_supported_token_program(program_id: &Pubkey) -> bool {
+    let is_spl_token = *program_id == anchor_spl::token::ID;
+    let is_token_2022 = *program_id == anchor_spl::token_2022::ID;
+
+    if cfg!(feature = "spl-token-only") {
+        is_spl_token
+    } else if cfg!(feature = "token-2022-only") {
+        is_token_2022
+    } else {
+        is_spl_token || is_token_2022
+    }
+}
+
 // Cost calculation utilities
-pub fn calculate_mint_cost(amount: u64, decimals: u8) -> u64 {
-    // Simple cost calculation - can be enhanced based on business logic
-    let base_cost = 1000; // Base cost in lamports
-    let amount_factor = amount / (10_u64.pow(decimals as u32));
-    base_cost + amount_factor
+/// Lamports for `amount` base units when `sold` have already been sold along `curve`
+pub fn calculate_mint_cost(curve: &PriceCurve, sold: u64, amount: u64, decimals: u8) -> Result<u64> {
+    let end = sold.checked_add(amount).ok_or(TokenError::SupplyOverflow)?;
+    Ok(curve
+        .reserve(end, decimals)?
+        .saturating_sub(curve.reserve(sold, decimals)?))
+}
+
+/// `base^exp` for a `PRICE_SCALE` fixed-point base, or None on overflow
+pub fn pow_scaled(mut base: u128, mut exp: u64) -> Option<u128> {
+    let mut result = PRICE_SCALE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base)? / PRICE_SCALE;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base)? / PRICE_SCALE;
+        }
+    }
+    Some(result)
 }
 
 // Token supply utilities
@@ -946,38 +7797,149 @@ pub fn validate_batch_destinations(destinations: &[MintDestination]) -> Result<(
     Ok(())
 }
 
+pub fn calculate_batch_total(destinations: &[MintDestination]) -> Result<u64> {
+    destinations
+        .iter()
+        .try_fold(0u64, |total, dest| total.checked_add(dest.amount))
+        .ok_or(TokenError::SupplyOverflow.into())
+}
+
 // Constants for the program
 pub const MAX_BATCH_SIZE: usize = 10;
+pub const BATCH_MINT_ACCOUNTS_PER_DESTINATION: usize = 2;
+pub const MAX_BATCH_FREEZE_SIZE: usize = 25; // keeps the skipped list within return data limits
+pub const BATCH_FREEZE_ACCOUNTS_PER_ENTRY: usize = 2;
+pub const BATCH_THAW_ACCOUNTS_PER_ENTRY: usize = 3;
 pub const MAX_URI_LENGTH: usize = 200;
 pub const MAX_NAME_LENGTH: usize = 32;
 pub const MAX_SYMBOL_LENGTH: usize = 10;
+pub const MAX_CASE_ID_LENGTH: usize = 64;
+pub const MAX_CREATORS: usize = 5;
+pub const MINT_STATE_SEED: &[u8] = b"mint_state";
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const PENDING_AUTHORITY_SEED: &[u8] = b"pending_authority";
+pub const QUEUED_CHANGE_SEED: &[u8] = b"queued_change";
+pub const ROLES_SEED: &[u8] = b"roles";
+pub const MAX_ROLE_GRANTS: usize = 16;
+pub const RATE_LIMIT_SEED: &[u8] = b"rate_limit";
+pub const MAX_RATE_LIMITED_MINTERS: usize = 16;
+pub const DISTRIBUTOR_SEED: &[u8] = b"distributor";
+pub const MAX_AIRDROP_RECIPIENTS: usize = 65_536;
+pub const VESTING_SEED: &[u8] = b"vesting";
+pub const SALE_SEED: &[u8] = b"sale";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const SALE_PURCHASE_SEED: &[u8] = b"sale_purchase";
+pub const PRESALE_SEED: &[u8] = b"presale";
+pub const FAUCET_SEED: &[u8] = b"faucet";
+pub const FAUCET_CLAIM_SEED: &[u8] = b"faucet_claim";
+pub const FREEZE_RECORD_SEED: &[u8] = b"freeze_record";
+pub const DENYLIST_SEED: &[u8] = b"denylist";
+pub const KYC_CONFIG_SEED: &[u8] = b"kyc_config";
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+pub const MAX_KYC_ATTESTERS: usize = 8;
+pub const PRICE_SCALE: u128 = 1_000_000_000;
+pub const BPS_DENOMINATOR: u128 = 10_000;
+pub const MAX_AUTHORITY_DELAY: i64 = 30 * 24 * 60 * 60; // 30 days
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
 pub const MIN_RENT_EXEMPT_BALANCE: u64 = 2039280; // Approximate rent for token account
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_token_metadata_validation() {
-        let valid_metadata = TokenMetadata {
+    fn test_metadata() -> TokenMetadata {
+        TokenMetadata {
             name: "Test Token".to_string(),
             symbol: "TEST".to_string(),
             uri: "https://example.com/token.json".to_string(),
             seller_fee_basis_points: 500,
-        };
+            creators: None,
+            collection: None,
+            uses: None,
+            is_mutable: true,
+        }
+    }
+
+    #[test]
+    fn test_token_metadata_validation() {
+        let valid_metadata = test_metadata();
         
         assert!(valid_metadata.validate().is_ok());
 
         let invalid_metadata = TokenMetadata {
             name: "".to_string(), // Empty name should fail
-            symbol: "TEST".to_string(),
-            uri: "https://example.com/token.json".to_string(),
-            seller_fee_basis_points: 500,
+            ..test_metadata()
         };
         
         assert!(invalid_metadata.validate().is_err());
     }
 
+    #[test]
+    fn test_metadata_creators_validation() {
+        let creator = |share| MetadataCreator {
+            address: Pubkey::new_unique(),
+            verified: false,
+            share,
+        };
+
+        let valid_metadata = TokenMetadata {
+            creators: Some(vec![creator(60), creator(40)]),
+            ..test_metadata()
+        };
+        assert!(valid_metadata.validate().is_ok());
+
+        let bad_shares = TokenMetadata {
+            creators: Some(vec![creator(60), creator(30)]),
+            ..test_metadata()
+        };
+        assert!(bad_shares.validate().is_err());
+
+        let too_many = TokenMetadata {
+            creators: Some(vec![creator(20); 6]),
+            ..test_metadata()
+        };
+        assert!(too_many.validate().is_err());
+    }
+
+    #[test]
+    fn test_metadata_update_authorization() {
+        let authority = Pubkey::new_unique();
+        assert!(validate_metadata_update(&authority, true, &authority).is_ok());
+        assert_eq!(
+            validate_metadata_update(&authority, true, &Pubkey::new_unique()).unwrap_err(),
+            TokenError::Unauthorized.into()
+        );
+        assert_eq!(
+            validate_metadata_update(&authority, false, &authority).unwrap_err(),
+            TokenError::MetadataImmutable.into()
+        );
+    }
+
+    #[test]
+    fn test_metadata_data_v2_encoding() {
+        let creator = Pubkey::new_unique();
+        let metadata = TokenMetadata {
+            creators: Some(vec![MetadataCreator {
+                address: creator,
+                verified: false,
+                share: 100,
+            }]),
+            uses: Some(MetadataUses {
+                use_method: MetadataUseMethod::Multiple,
+                remaining: 5,
+                total: 5,
+            }),
+            ..test_metadata()
+        };
+
+        let data = metadata.to_data_v2();
+        assert_eq!(data.name, "Test Token");
+        assert_eq!(data.creators.unwrap()[0].address, creator);
+        assert_eq!(data.uses.unwrap().use_method, UseMethod::Multiple);
+        assert!(data.collection.is_none());
+    }
+
     #[test]
     fn test_mint_destination_validation() {
         let valid_dest = MintDestination::new(Pubkey::default(), 1000);
@@ -998,6 +7960,505 @@ mod tests {
         assert!(calculate_total_supply_after_mint(max_supply, safe_mint).is_ok());
     }
 
+    fn test_mint_state(max_supply: u64) -> TokenMintState {
+        TokenMintState {
+            mint: Pubkey::new_unique(),
+            mint_authority: Pubkey::new_unique(),
+            total_minted: 0,
+            max_supply,
+            authority_delay: 0,
+            paused: false,
+            rate_limited: false,
+            compliance_authority: None,
+            kyc_required: false,
+            default_frozen: false,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_supply_cap_check() {
+        let state = test_mint_state(1_000);
+
+        assert_eq!(state.check_supply_cap(400, 600).unwrap(), 1_000);
+        assert!(state.check_supply_cap(400, 601).is_err());
+        assert!(test_mint_state(u64::MAX).check_supply_cap(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_supply_cap_can_only_be_lowered() {
+        let mut state = test_mint_state(1_000);
+
+        assert!(state.lower_max_supply(1_001, 0).is_err());
+        assert!(state.lower_max_supply(499, 500).is_err());
+        assert!(state.lower_max_supply(800, 500).is_ok());
+        assert_eq!(state.max_supply, 800);
+        assert!(state.lower_max_supply(900, 500).is_err());
+    }
+
+    #[test]
+    fn test_paused_state() {
+        let mut state = test_mint_state(u64::MAX);
+        assert!(state.require_not_paused().is_ok());
+
+        state.paused = true;
+        assert!(state.require_not_paused().is_err());
+    }
+
+    fn test_rate_limit(mint_limit: u64, minter_limit: u64) -> RateLimit {
+        RateLimit {
+            mint: Pubkey::new_unique(),
+            window_seconds: 86_400,
+            mint_limit,
+            minter_limit,
+            mint_bucket: RateBucket::full(mint_limit, 86_400, 0),
+            minters: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_window() {
+        let minter = Pubkey::new_unique();
+        let mut rate_limit = test_rate_limit(1_000, 600);
+
+        rate_limit.consume(&minter, 600, 0).unwrap();
+        assert!(rate_limit.consume(&minter, 1, 0).is_err());
+        assert_eq!(rate_limit.remaining(&minter, 0), (400, 0));
+
+        // Capacity refills linearly over the window
+        assert_eq!(rate_limit.remaining(&minter, 43_200), (900, 300));
+        assert_eq!(rate_limit.remaining(&minter, 86_400), (1_000, 600));
+        assert_eq!(rate_limit.remaining(&minter, 10 * 86_400), (1_000, 600));
+
+        // The per-mint limit applies across minters
+        let other = Pubkey::new_unique();
+        rate_limit.consume(&other, 400, 0).unwrap();
+        assert!(rate_limit.consume(&Pubkey::new_unique(), 1, 0).is_err());
+        assert_eq!(rate_limit.remaining(&other, 0), (0, 200));
+    }
+
+    #[test]
+    fn test_rate_limit_keeps_partial_refills() {
+        // One unit refills every 86.4s; minting every 60s must not lose the remainder
+        let minter = Pubkey::new_unique();
+        let mut rate_limit = test_rate_limit(1_000, 1_000);
+        rate_limit.consume(&minter, 1_000, 0).unwrap();
+
+        let mut minted = 0;
+        for now in (60..=86_400).step_by(60) {
+            let (available, _) = rate_limit.remaining(&minter, now);
+            if available > 0 {
+                rate_limit.consume(&minter, available, now).unwrap();
+                minted += available;
+            }
+        }
+        assert_eq!(minted, 1_000);
+    }
+
+    #[test]
+    fn test_rate_limit_can_only_be_tightened() {
+        let minter = Pubkey::new_unique();
+        let mut rate_limit = test_rate_limit(1_000, 600);
+        rate_limit.consume(&minter, 500, 0).unwrap();
+
+        assert!(rate_limit.tighten(86_400, u64::MAX, 600, 0).is_err());
+        assert!(rate_limit.tighten(86_400, 1_000, 601, 0).is_err());
+        assert!(rate_limit.tighten(86_399, 1_000, 600, 0).is_err());
+
+        // Used capacity is carried over rather than reset
+        rate_limit.tighten(2 * 86_400, 800, 400, 0).unwrap();
+        assert_eq!(rate_limit.remaining(&minter, 0), (500, 100));
+        assert_eq!(rate_limit.remaining(&minter, 2 * 86_400), (800, 400));
+    }
+
+    #[test]
+    fn test_rate_limit_minter_slots_are_recycled() {
+        let mut rate_limit = test_rate_limit(u64::MAX, 10);
+        for _ in 0..MAX_RATE_LIMITED_MINTERS {
+            rate_limit.consume(&Pubkey::new_unique(), 10, 0).unwrap();
+        }
+        assert_eq!(
+            rate_limit.consume(&Pubkey::new_unique(), 1, 0).unwrap_err(),
+            TokenError::RateLimitTableFull.into()
+        );
+
+        // Once earlier minters have refilled their slots can be reused
+        rate_limit.consume(&Pubkey::new_unique(), 1, 86_400).unwrap();
+        assert_eq!(rate_limit.minters.len(), 1);
+
+        assert!(RateLimit::validate_config(0, 1, 1).is_err());
+        assert!(RateLimit::validate_config(60, 0, 1).is_err());
+        assert!(RateLimit::validate_config(60, 1, 1).is_ok());
+    }
+
+    fn merkle_node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (left, right) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[1], &left, &right]).to_bytes()
+    }
+
+    #[test]
+    fn test_airdrop_claims() {
+        let claimants: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = claimants
+            .iter()
+            .enumerate()
+            .map(|(i, claimant)| airdrop_leaf(i as u64, claimant, 100 * (i as u64 + 1)))
+            .collect();
+        // Odd leaf is promoted unchanged
+        let left = merkle_node(leaves[0], leaves[1]);
+        let root = merkle_node(left, leaves[2]);
+
+        let mut distributor = Distributor {
+            mint: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            merkle_root: root,
+            total_allocation: 600,
+            total_claimed: 0,
+            num_recipients: 3,
+            expires_at: 1_000,
+            bump: 255,
+            claimed: vec![0; Distributor::bitmap_len(3)],
+        };
+
+        assert!(distributor.claim(1, &claimants[1], 300, &[leaves[0], leaves[2]]).is_err());
+        assert!(distributor.claim(1, &claimants[0], 200, &[leaves[0], leaves[2]]).is_err());
+        distributor.claim(1, &claimants[1], 200, &[leaves[0], leaves[2]]).unwrap();
+        assert!(distributor.is_claimed(1));
+        assert!(!distributor.is_claimed(0));
+        assert!(distributor.claim(1, &claimants[1], 200, &[leaves[0], leaves[2]]).is_err());
+
+        distributor.claim(2, &claimants[2], 300, &[left]).unwrap();
+        distributor.claim(0, &claimants[0], 100, &[leaves[1], leaves[2]]).unwrap();
+        assert_eq!(distributor.total_claimed, 600);
+        assert!(distributor.claim(3, &claimants[0], 100, &[]).is_err());
+    }
+
+    #[test]
+    fn test_airdrop_config_validation() {
+        assert!(Distributor::validate_config(1_000, 10, 100, 50).is_ok());
+        assert!(Distributor::validate_config(0, 10, 100, 50).is_err());
+        assert!(Distributor::validate_config(1_000, 0, 100, 50).is_err());
+        assert!(Distributor::validate_config(1_000, 10, 50, 50).is_err());
+        assert!(Distributor::validate_config(1_000, MAX_AIRDROP_RECIPIENTS as u32 + 1, 100, 50).is_err());
+        assert!(Distributor::space(MAX_AIRDROP_RECIPIENTS as u32) <= 10_240);
+        assert_eq!(Distributor::bitmap_len(9), 2);
+    }
+
+    #[test]
+    fn test_presale_allowlist() {
+        let wallets: Vec<Pubkey> = (0..2).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets.iter().map(|w| allowlist_leaf(w, 500)).collect();
+        let presale = Presale {
+            mint: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            merkle_root: merkle_node(leaves[0], leaves[1]),
+            price: 3,
+            start_ts: 0,
+            end_ts: 100,
+            total_sold: 0,
+            treasury_bump: 255,
+            bump: 255,
+        };
+
+        presale.verify_allowance(&wallets[0], 500, &[leaves[1]]).unwrap();
+        assert!(presale.verify_allowance(&wallets[0], 501, &[leaves[1]]).is_err());
+        assert!(presale.verify_allowance(&Pubkey::new_unique(), 500, &[leaves[1]]).is_err());
+        assert_eq!(presale.cost(200).unwrap(), 600);
+        assert!(presale.cost(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_faucet_cooldown() {
+        let mut faucet = Faucet {
+            mint: Pubkey::new_unique(),
+            drip_amount: 1_000,
+            cooldown_seconds: 3_600,
+            enabled: true,
+            bump: 255,
+        };
+        let mut claim = FaucetClaim {
+            mint: faucet.mint,
+            wallet: Pubkey::new_unique(),
+            last_drip_at: None,
+            bump: 255,
+        };
+
+        assert_eq!(faucet.drip(&mut claim, 100).unwrap(), 3_700);
+        assert!(faucet.drip(&mut claim, 3_699).is_err());
+        assert_eq!(faucet.drip(&mut claim, 3_700).unwrap(), 7_300);
+
+        faucet.enabled = false;
+        assert!(faucet.drip(&mut claim, 100_000).is_err());
+        assert_eq!(claim.last_drip_at, Some(3_700));
+    }
+
+    #[test]
+    fn test_spender_authorization() {
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let mut account = SplTokenAccount {
+            owner,
+            amount: 1_000,
+            ..Default::default()
+        };
+
+        assert!(authorize_spender(&account, &owner, 1_000).is_ok());
+        assert!(authorize_spender(&account, &delegate, 1).is_err());
+
+        account.delegate = Some(delegate).into();
+        account.delegated_amount = 300;
+        assert!(authorize_spender(&account, &delegate, 300).is_ok());
+        assert!(authorize_spender(&account, &delegate, 301).is_err());
+        assert!(authorize_spender(&account, &Pubkey::new_unique(), 1).is_err());
+    }
+
+    fn test_vesting(revocable: bool) -> Vesting {
+        Vesting {
+            mint: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            total_amount: 1_000,
+            released: 0,
+            start_ts: 0,
+            cliff_ts: 250,
+            end_ts: 1_000,
+            revocable,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_vesting_schedule() {
+        let mut vesting = test_vesting(false);
+
+        assert_eq!(vesting.vested_amount(249), 0);
+        assert_eq!(vesting.vested_amount(250), 250);
+        assert_eq!(vesting.vested_amount(500), 500);
+        assert_eq!(vesting.vested_amount(5_000), 1_000);
+
+        assert!(vesting.release(100).is_err());
+        assert_eq!(vesting.release(400).unwrap(), 400);
+        assert!(vesting.release(400).is_err());
+        assert_eq!(vesting.release(2_000).unwrap(), 600);
+        assert!(vesting.revoke(2_000).is_err());
+
+        assert!(Vesting::validate_schedule(1, 0, 0, 1).is_ok());
+        assert!(Vesting::validate_schedule(0, 0, 0, 1).is_err());
+        assert!(Vesting::validate_schedule(1, 0, 2, 1).is_err());
+        assert!(Vesting::validate_schedule(1, 5, 5, 5).is_err());
+    }
+
+    #[test]
+    fn test_vesting_revocation() {
+        let mut vesting = test_vesting(true);
+        assert_eq!(vesting.release(400).unwrap(), 400);
+
+        assert_eq!(vesting.revoke(600).unwrap(), 400);
+        assert_eq!(vesting.total_amount, 600);
+        assert!(vesting.revoke(600).is_err());
+
+        // Vested tokens stay releasable, but nothing more vests
+        assert_eq!(vesting.release(5_000).unwrap(), 200);
+        assert!(vesting.release(10_000).is_err());
+
+        // Revoking before the cliff returns everything
+        let mut vesting = test_vesting(true);
+        assert_eq!(vesting.revoke(100).unwrap(), 1_000);
+        assert!(vesting.release(5_000).is_err());
+    }
+
+    #[test]
+    fn test_authority_delay_can_only_be_raised() {
+        let mut state = test_mint_state(u64::MAX);
+        assert!(state.require_no_timelock().is_ok());
+
+        assert!(state.raise_authority_delay(-1).is_err());
+        assert!(state.raise_authority_delay(MAX_AUTHORITY_DELAY + 1).is_err());
+        assert!(state.raise_authority_delay(3_600).is_ok());
+        assert!(state.require_no_timelock().is_err());
+        assert!(state.raise_authority_delay(60).is_err());
+        assert!(state.raise_authority_delay(MAX_AUTHORITY_DELAY).is_ok());
+    }
+
+    #[test]
+    fn test_queued_change_timelock() {
+        let queued = QueuedAuthorityChange {
+            mint: Pubkey::new_unique(),
+            kind: AuthorityKind::Mint,
+            new_authority: Some(Pubkey::new_unique()),
+            queued_by: Pubkey::new_unique(),
+            execute_after: 1_000,
+            bump: 255,
+        };
+
+        assert!(!queued.is_ready(999));
+        assert!(queued.is_ready(1_000));
+        assert_eq!(queued.try_to_vec().unwrap().len() + 8, QueuedAuthorityChange::LEN);
+    }
+
+    #[test]
+    fn test_role_grants_and_allowances() {
+        let admin = Pubkey::new_unique();
+        let minter = Pubkey::new_unique();
+        let mut roles = Roles {
+            mint: Pubkey::new_unique(),
+            grants: vec![RoleGrant { holder: admin, role: Role::Admin, allowance: None }],
+            bump: 255,
+        };
+
+        roles.grant(minter, Role::Minter, Some(1_000)).unwrap();
+        assert!(roles.has_role(&minter, Role::Minter));
+        assert!(!roles.has_role(&minter, Role::Freezer));
+
+        roles.consume(&minter, Role::Minter, 600).unwrap();
+        assert!(roles.consume(&minter, Role::Minter, 401).is_err());
+        roles.consume(&minter, Role::Minter, 400).unwrap();
+        assert!(roles.consume(&minter, Role::Minter, 1).is_err());
+        assert!(roles.consume(&minter, Role::Pauser, 1).is_err());
+        roles.consume(&admin, Role::Admin, u64::MAX).unwrap();
+
+        // Re-granting replaces the allowance instead of adding a second grant
+        roles.grant(minter, Role::Minter, Some(50)).unwrap();
+        assert_eq!(roles.grants.len(), 2);
+        roles.consume(&minter, Role::Minter, 50).unwrap();
+
+        roles.revoke(&minter, Role::Minter).unwrap();
+        assert!(roles.revoke(&minter, Role::Minter).is_err());
+        assert!(roles.revoke(&admin, Role::Admin).is_err());
+    }
+
+    #[test]
+    fn test_role_grant_limit() {
+        let mut roles = Roles {
+            mint: Pubkey::new_unique(),
+            grants: Vec::new(),
+            bump: 255,
+        };
+        for _ in 0..MAX_ROLE_GRANTS {
+            roles.grant(Pubkey::new_unique(), Role::Minter, None).unwrap();
+        }
+        assert!(roles.grant(Pubkey::new_unique(), Role::Minter, None).is_err());
+
+        let grant = RoleGrant { holder: Pubkey::new_unique(), role: Role::Admin, allowance: Some(1) };
+        assert_eq!(grant.try_to_vec().unwrap().len(), RoleGrant::LEN);
+    }
+
+    #[test]
+    fn test_multisig_config_validation() {
+        let signers: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+        assert!(Multisig::validate_config(&signers, 2).is_ok());
+        assert!(Multisig::validate_config(&signers, 0).is_err());
+        assert!(Multisig::validate_config(&signers, 4).is_err());
+        assert!(Multisig::validate_config(&[], 1).is_err());
+        assert!(Multisig::validate_config(&[signers[0], signers[0]], 1).is_err());
+
+        let too_many: Vec<Pubkey> = (0..11).map(|_| Pubkey::new_unique()).collect();
+        assert!(Multisig::validate_config(&too_many, 1).is_err());
+    }
+
+    #[test]
+    fn test_multisig_proposal_approvals() {
+        let signers: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let multisig = Multisig {
+            mint: Pubkey::new_unique(),
+            signers: signers.clone(),
+            threshold: 2,
+            proposal_count: 1,
+            bump: 255,
+        };
+        let mut proposal = MultisigProposal {
+            multisig: Pubkey::new_unique(),
+            index: 0,
+            proposer: signers[0],
+            action: MultisigAction::FreezeAccount {
+                token_account: Pubkey::new_unique(),
+                reason: FreezeReason::Fraud,
+                case_id: String::from("CASE-1"),
+            },
+            approvals: Vec::new(),
+            executed: false,
+            bump: 255,
+        };
+
+        assert_eq!(proposal.approve(&multisig, &signers[0]).unwrap(), 1);
+        assert!(!proposal.is_approved(&multisig));
+        assert!(proposal.approve(&multisig, &signers[0]).is_err());
+        assert!(proposal.approve(&multisig, &Pubkey::new_unique()).is_err());
+        assert_eq!(proposal.approve(&multisig, &signers[1]).unwrap(), 2);
+        assert!(proposal.is_approved(&multisig));
+
+        proposal.executed = true;
+        assert!(proposal.approve(&multisig, &signers[2]).is_err());
+    }
+
+    #[test]
+    fn test_multisig_action_serialized_size() {
+        let action = MultisigAction::MintTokens {
+            destination: Pubkey::new_unique(),
+            amount: u64::MAX,
+        };
+        assert!(action.try_to_vec().unwrap().len() <= MultisigAction::LEN);
+
+        let action = MultisigAction::FreezeAccount {
+            token_account: Pubkey::new_unique(),
+            reason: FreezeReason::CourtOrder,
+            case_id: "C".repeat(MAX_CASE_ID_LENGTH),
+        };
+        assert_eq!(action.try_to_vec().unwrap().len(), MultisigAction::LEN);
+        assert!(action.validate().is_ok());
+
+        let action = MultisigAction::FreezeAccount {
+            token_account: Pubkey::new_unique(),
+            reason: FreezeReason::Other,
+            case_id: "C".repeat(MAX_CASE_ID_LENGTH + 1),
+        };
+        assert!(action.validate().is_err());
+
+        let action = MultisigAction::SetMintAuthority { new_authority: Some(Pubkey::new_unique()) };
+        assert!(action.try_to_vec().unwrap().len() <= MultisigAction::LEN);
+
+        let action = MultisigAction::QueueAuthorityChange {
+            kind: AuthorityKind::Freeze,
+            new_authority: Some(Pubkey::new_unique()),
+        };
+        assert!(action.try_to_vec().unwrap().len() <= MultisigAction::LEN);
+        assert_eq!(action.token_account(), None);
+
+        let action = MultisigAction::ProposeAuthority {
+            kind: AuthorityKind::Mint,
+            new_authority: Pubkey::new_unique(),
+        };
+        assert!(action.try_to_vec().unwrap().len() <= MultisigAction::LEN);
+        assert_eq!(action.token_account(), None);
+    }
+
+    #[test]
+    fn test_immediate_authority_changes() {
+        let mint_state = Pubkey::new_unique();
+        let multisig = Pubkey::new_unique();
+
+        assert!(is_immediate_authority_change(None, mint_state, None));
+        assert!(is_immediate_authority_change(None, mint_state, Some(multisig)));
+        assert!(is_immediate_authority_change(Some(mint_state), mint_state, None));
+        assert!(is_immediate_authority_change(Some(multisig), mint_state, Some(multisig)));
+        assert!(!is_immediate_authority_change(Some(Pubkey::new_unique()), mint_state, Some(multisig)));
+        assert!(!is_immediate_authority_change(Some(multisig), mint_state, None));
+    }
+
+    #[test]
+    fn test_pending_authority_seeds_differ_by_kind() {
+        let mint = Pubkey::new_unique();
+        let pda = |kind: AuthorityKind| {
+            Pubkey::find_program_address(&[PENDING_AUTHORITY_SEED, mint.as_ref(), kind.seed()], &crate::ID).0
+        };
+
+        assert_ne!(pda(AuthorityKind::Mint), pda(AuthorityKind::Freeze));
+        assert!(matches!(AuthorityType::from(AuthorityKind::Freeze), AuthorityType::FreezeAccount));
+    }
+
     #[test]
     fn test_batch_validation() {
         let valid_batch = vec![
@@ -1013,12 +8474,227 @@ mod tests {
         assert!(validate_batch_destinations(&oversized_batch).is_err());
     }
 
+    #[test]
+    fn test_unique_accounts() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(require_unique_accounts([a, b].iter()).is_ok());
+        assert_eq!(
+            require_unique_accounts([a, b, a].iter()).unwrap_err(),
+            TokenError::DuplicateAccount.into()
+        );
+    }
+
+    #[test]
+    fn test_batch_total_calculation() {
+        let batch = vec![
+            MintDestination::new(Pubkey::default(), 100),
+            MintDestination::new(Pubkey::default(), 200),
+        ];
+        assert_eq!(calculate_batch_total(&batch).unwrap(), 300);
+
+        let overflowing_batch = vec![
+            MintDestination::new(Pubkey::default(), u64::MAX),
+            MintDestination::new(Pubkey::default(), 1),
+        ];
+        assert!(calculate_batch_total(&overflowing_batch).is_err());
+    }
+
+    #[test]
+    fn test_supported_token_programs() {
+        assert!(!is_supported_token_program(&Pubkey::new_unique()));
+        assert_eq!(
+            is_supported_token_program(&anchor_spl::token::ID),
+            !cfg!(feature = "token-2022-only")
+        );
+        assert_eq!(
+            is_supported_token_program(&anchor_spl::token_2022::ID),
+            !cfg!(feature = "spl-token-only")
+        );
+    }
+
+    #[test]
+    fn test_freeze_record_case_id() {
+        assert!(FreezeRecord::validate_case_id("CASE-2024-0042").is_ok());
+        assert!(FreezeRecord::validate_case_id(&"x".repeat(MAX_CASE_ID_LENGTH)).is_ok());
+        assert!(FreezeRecord::validate_case_id(&"x".repeat(MAX_CASE_ID_LENGTH + 1)).is_err());
+
+        let record = FreezeRecord {
+            mint: Pubkey::new_unique(),
+            account: Pubkey::new_unique(),
+            reason: FreezeReason::CourtOrder,
+            case_id: "x".repeat(MAX_CASE_ID_LENGTH),
+            frozen_at: 1_700_000_000,
+            frozen_by: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        record.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), FreezeRecord::LEN);
+    }
+
+    #[test]
+    fn test_denylist_gating() {
+        let mut mint_state = test_mint_state(u64::MAX);
+        assert_eq!(batch_accounts_per_destination(&mint_state, false), BATCH_MINT_ACCOUNTS_PER_DESTINATION);
+        // No compliance authority: entries are not required
+        assert!(require_not_denylisted(&mint_state, None).is_ok());
+
+        mint_state.compliance_authority = Some(Pubkey::new_unique());
+        assert_eq!(batch_accounts_per_destination(&mint_state, false), BATCH_MINT_ACCOUNTS_PER_DESTINATION + 1);
+        assert!(require_not_denylisted(&mint_state, None).is_err());
+
+        let entry = DenylistEntry {
+            mint: mint_state.mint,
+            wallet: Pubkey::new_unique(),
+            case_id: "x".repeat(MAX_CASE_ID_LENGTH),
+            added_by: Pubkey::new_unique(),
+            added_at: 0,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        entry.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), DenylistEntry::LEN);
+    }
+
+    #[test]
+    fn test_default_frozen_accounts() {
+        let mut mint_state = test_mint_state(u64::MAX);
+        assert!(!needs_default_freeze(&mint_state));
+
+        mint_state.default_frozen = true;
+        assert!(needs_default_freeze(&mint_state));
+        assert_eq!(batch_accounts_per_destination(&mint_state, true), BATCH_MINT_ACCOUNTS_PER_DESTINATION + 1);
+
+        assert_eq!(mint_account_space(&anchor_spl::token::ID, true).unwrap(), 82);
+        assert_eq!(mint_account_space(&anchor_spl::token_2022::ID, false).unwrap(), 82);
+        assert_eq!(mint_account_space(&anchor_spl::token_2022::ID, true).unwrap(), 171);
+    }
+
+    #[test]
+    fn test_kyc_allowlist() {
+        let mut mint_state = test_mint_state(u64::MAX);
+        // Allowlist off: attestations are not required
+        assert!(require_attested(&mint_state, None, 0).is_ok());
+
+        mint_state.kyc_required = true;
+        mint_state.compliance_authority = Some(Pubkey::new_unique());
+        assert_eq!(batch_accounts_per_destination(&mint_state, false), BATCH_MINT_ACCOUNTS_PER_DESTINATION + 2);
+        assert!(require_attested(&mint_state, None, 0).is_err());
+
+        assert!(Attestation::validate(b"US", 100, 50).is_ok());
+        assert!(Attestation::validate(b"US", 50, 50).is_err());
+        assert!(Attestation::validate(b"us", 100, 50).is_err());
+
+        let attestation = Attestation {
+            mint: mint_state.mint,
+            wallet: Pubkey::new_unique(),
+            attester: Pubkey::new_unique(),
+            jurisdiction: *b"DE",
+            issued_at: 0,
+            expires_at: 100,
+            bump: 255,
+        };
+        assert!(attestation.is_valid(99));
+        assert!(!attestation.is_valid(100));
+        let mut data = Vec::new();
+        attestation.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), Attestation::LEN);
+
+        let mut kyc_config = KycConfig { mint: mint_state.mint, attesters: Vec::new(), bump: 255 };
+        for _ in 0..MAX_KYC_ATTESTERS {
+            kyc_config.add(Pubkey::new_unique()).unwrap();
+        }
+        assert!(kyc_config.add(Pubkey::new_unique()).is_err());
+        let attester = kyc_config.attesters[0];
+        kyc_config.remove(&attester).unwrap();
+        assert!(!kyc_config.is_attester(&attester));
+        assert!(kyc_config.remove(&attester).is_err());
+        let mut data = Vec::new();
+        kyc_config.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= KycConfig::LEN);
+    }
+
+    #[test]
+    fn test_checked_variants_required_for_token_2022() {
+        assert!(require_legacy_token_program(&anchor_spl::token::ID).is_ok());
+        assert!(require_legacy_token_program(&anchor_spl::token_2022::ID).is_err());
+    }
+
     #[test]
     fn test_cost_calculation() {
-        let cost = calculate_mint_cost(1000, 6);
-        assert!(cost > 0);
-        
-        let high_amount_cost = calculate_mint_cost(1_000_000, 6);
-        assert!(high_amount_cost > cost);
+        let fixed = PriceCurve::Fixed { price: 1_000 };
+        assert_eq!(calculate_mint_cost(&fixed, 0, 1_000_000, 6).unwrap(), 1_000);
+        assert_eq!(calculate_mint_cost(&fixed, 5_000_000, 2_500_000, 6).unwrap(), 2_500);
+
+        // 100 + 10 * s per whole token: the first two tokens cost 100 * 2 + 10 * 2^2 / 2
+        let linear = PriceCurve::Linear { base_price: 100, slope: 10 };
+        assert_eq!(calculate_mint_cost(&linear, 0, 2_000_000, 6).unwrap(), 220);
+        assert_eq!(calculate_mint_cost(&linear, 1_000_000, 1_000_000, 6).unwrap(), 115);
+
+        // Doubling price per whole token: 1000, 2000, 4000
+        let exponential = PriceCurve::Exponential { base_price: 1_000, growth_bps: 10_000 };
+        assert_eq!(calculate_mint_cost(&exponential, 0, 3_000_000, 6).unwrap(), 7_000);
+        assert_eq!(calculate_mint_cost(&exponential, 1_000_000, 500_000, 6).unwrap(), 1_000);
+        assert!(calculate_mint_cost(&exponential, 0, u64::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn test_bonding_curve_costs_are_path_independent() {
+        let curves = [
+            PriceCurve::Linear { base_price: 1_000, slope: 7 },
+            PriceCurve::Exponential { base_price: 1_000_000, growth_bps: 25 },
+        ];
+        for curve in curves {
+            let whole = calculate_mint_cost(&curve, 0, 90_000_000_000, 9).unwrap();
+            let first = calculate_mint_cost(&curve, 0, 33_333_333_333, 9).unwrap();
+            let rest = calculate_mint_cost(&curve, 33_333_333_333, 56_666_666_667, 9).unwrap();
+            assert_eq!(first + rest, whole);
+            assert!(rest > first);
+        }
+
+        assert!(PriceCurve::Fixed { price: 0 }.validate().is_err());
+        assert!(PriceCurve::Exponential { base_price: 1, growth_bps: 0 }.validate().is_err());
+        assert!(!PriceCurve::Fixed { price: 1 }.is_bonding());
+    }
+
+    #[test]
+    fn test_sale_wallet_cap_and_window() {
+        let mut purchase = SalePurchase {
+            sale: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            purchased: 0,
+            bump: 255,
+        };
+        purchase.record(600, 1_000).unwrap();
+        assert!(purchase.record(401, 1_000).is_err());
+        purchase.record(400, 1_000).unwrap();
+
+        let sale = Sale {
+            mint: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            curve: PriceCurve::Fixed { price: 1 },
+            start_ts: 100,
+            end_ts: 200,
+            wallet_cap: u64::MAX,
+            sold: 0,
+            sell_back: false,
+            treasury_bump: 255,
+            bump: 255,
+        };
+        assert!(sale.require_active(99).is_err());
+        assert!(sale.require_active(100).is_ok());
+        assert!(sale.require_active(200).is_err());
+        assert_eq!(sale.sell_back_reserve(9, 150).unwrap(), 0);
+
+        // The sell-back reserve is released when the sale ends
+        let sale = Sale {
+            curve: PriceCurve::Linear { base_price: 1_000, slope: 100 },
+            sold: 2_000_000_000,
+            sell_back: true,
+            ..sale
+        };
+        assert!(sale.sell_back_reserve(9, 199).unwrap() > 0);
+        assert_eq!(sale.sell_back_reserve(9, 200).unwrap(), 0);
     }
 }
\ No newline at end of file